@@ -2,7 +2,6 @@ mod diagnostic;
 mod hir;
 pub mod lsp;
 mod source;
-mod structures;
 mod syntax;
 pub mod terminal;
 
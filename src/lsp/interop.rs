@@ -1,13 +1,14 @@
-pub(super) fn to_std_range(
+pub(super) fn to_span(
     db: &dyn crate::Db,
     file: crate::source::File,
     range: &lsp_types::Range,
-) -> std::ops::Range<usize> {
+) -> crate::source::Span {
     let lsp_types::Range { start, end } = range;
     let (start, end) = (location_from_lsp_position(*start), location_from_lsp_position(*end));
-    let (start, end) =
-        (file.location_utf16_to_index(db, start), file.location_utf16_to_index(db, end));
-    start.try_into().unwrap()..end.try_into().unwrap()
+    crate::source::Span::new(
+        file.location_utf16_to_index(db, start),
+        file.location_utf16_to_index(db, end),
+    )
 }
 
 fn location_from_lsp_position(position: lsp_types::Position) -> crate::source::LocationUtf16 {
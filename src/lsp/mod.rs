@@ -153,7 +153,7 @@ impl Server {
 }
 
 struct VirtualSource {
-    in_memory_files: foldhash::HashMap<std::path::PathBuf, String>,
+    in_memory_files: foldhash::HashMap<std::path::PathBuf, crate::source::File>,
 }
 
 impl VirtualSource {
@@ -161,14 +161,31 @@ impl VirtualSource {
         Self { in_memory_files: foldhash::HashMap::default() }
     }
 
-    fn add_or_set_file(&mut self, path: std::path::PathBuf, text: String) {
-        self.in_memory_files.insert(path, text);
+    fn add_or_set_file(&mut self, db: &dyn crate::Db, path: std::path::PathBuf, text: String) {
+        let file = crate::source::File::new(db, path.clone(), text, None);
+        self.in_memory_files.insert(path, file);
     }
 
-    fn get_file(&self, db: &dyn crate::Db, path: &std::path::Path) -> Option<crate::source::File> {
-        self.in_memory_files
-            .get(path)
-            .map(|text| crate::source::File::new(db, path.to_owned(), text.to_owned()))
+    /// Replace `range` of the text of an open file with `text`, keeping the
+    /// old file around so that only the edited part of it is parsed again.
+    fn edit_file(
+        &mut self,
+        db: &dyn crate::Db,
+        path: &std::path::Path,
+        range: &lsp_types::Range,
+        text: &str,
+    ) {
+        let file = self
+            .in_memory_files
+            .get_mut(path)
+            .expect("failed to get open path from virtual source");
+        let edit = interop::to_span(db, *file, range);
+        let mut file_text = file.text(db).clone();
+        file_text.replace_range(
+            usize::try_from(edit.start).unwrap()..usize::try_from(edit.end).unwrap(),
+            text,
+        );
+        *file = crate::source::File::new(db, path.to_owned(), file_text, Some((*file, edit)));
     }
 
     fn remove_file(&mut self, path: &std::path::Path) {
@@ -181,8 +198,8 @@ impl VirtualSource {
 
     fn to_source(&self, db: &dyn crate::Db) -> crate::source::Source {
         let mut files = Vec::new();
-        for (path, text) in &self.in_memory_files {
-            files.push(crate::source::File::new(db, path.clone(), text.clone()));
+        for &file in self.in_memory_files.values() {
+            files.push(file);
         }
         crate::source::Source::new(db, files)
     }
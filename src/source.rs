@@ -48,6 +48,10 @@ mod file {
         pub(crate) path: std::path::PathBuf,
         #[return_ref]
         pub(crate) text: String,
+        /// The file whose text was edited into this one, and the span of its
+        /// text which was replaced, so that this file can be parsed by parsing
+        /// only the part of the other which was edited.
+        pub(crate) previous: Option<(File, crate::source::Span)>,
     }
 
     #[salsa::tracked]
@@ -90,7 +94,7 @@ mod file {
 
 mod span {
     #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
-    pub(crate) struct Span {
+    pub struct Span {
         pub(crate) start: u32,
         pub(crate) end: u32,
     }
@@ -106,10 +110,6 @@ mod span {
         pub(crate) fn length(self) -> u32 {
             self.end - self.start
         }
-
-        pub(crate) fn join(self, other: Span) -> Span {
-            Self { start: self.start.min(other.start), end: self.end.max(other.end) }
-        }
     }
 
     impl core::fmt::Display for Span {
@@ -1 +1,688 @@
+//! Typed views of the nodes in an untyped syntax tree.
+//!
+//! Each view wraps a [`Node`], with accessors for the parts of the node. Since
+//! the tree may contain syntax errors, every part is optional, and a missing
+//! part is `None` rather than a panic.
+//!
+//! The views are generated by [`ast!`] from a description of each node kind,
+//! which must name a [`Kind`], so that the two can't drift apart.
 
+use super::{
+    kind::{Kind, t},
+    tree::Node,
+};
+use crate::source::{Span, Symbol};
+
+/// A typed view of a node in a syntax tree.
+pub(crate) trait AstNode: Clone {
+    /// View `node` as this type, if it is the right kind of node.
+    fn cast(node: Node) -> Option<Self>;
+
+    /// The node being viewed.
+    fn node(&self) -> &Node;
+}
+
+/// A token in a syntax tree, such as the name of a function.
+#[derive(Clone)]
+pub(crate) struct Token {
+    node: Node,
+}
+
+impl Token {
+    pub(crate) fn kind(&self) -> Kind {
+        self.node.kind()
+    }
+
+    pub(crate) fn span(&self) -> Span {
+        self.node.span()
+    }
+
+    pub(crate) fn text(&self) -> String {
+        self.node.text()
+    }
+}
+
+fn children<N: AstNode>(node: &Node) -> impl Iterator<Item = N> + use<N> {
+    node.children().filter_map(N::cast)
+}
+
+/// The `n`th child of `node` which is itself a node, if it has the type `N`.
+/// This distinguishes children of the same type, such as the operands of a
+/// binary expression.
+fn nth_child<N: AstNode>(node: &Node, n: usize) -> Option<N> {
+    let child = node.children().filter(|child| !child.kind().is_token()).nth(n)?;
+    N::cast(child)
+}
+
+/// Like [`nth_child`], but only counting the children before the first `token`,
+/// or all of them if there is no such token. Since a missing part leaves no
+/// node behind, this keeps a part which is missing before the token from
+/// shifting the parts after it.
+fn nth_child_before<N: AstNode>(node: &Node, token: Kind, n: usize) -> Option<N> {
+    let child = node
+        .children()
+        .take_while(|child| child.kind() != token)
+        .filter(|child| !child.kind().is_token())
+        .nth(n)?;
+    N::cast(child)
+}
+
+/// The first child of `node` after its first `token` which is itself a node,
+/// if it has the type `N`.
+fn child_after<N: AstNode>(node: &Node, token: Kind) -> Option<N> {
+    let child = node
+        .children()
+        .skip_while(|child| child.kind() != token)
+        .skip(1)
+        .find(|child| !child.kind().is_token())?;
+    N::cast(child)
+}
+
+fn tokens(node: &Node, kinds: &'static [Kind]) -> impl Iterator<Item = Token> + use<> {
+    node.children().filter(|child| kinds.contains(&child.kind())).map(|node| Token { node })
+}
+
+ast! {
+    nodes {
+        /// A whole file, which is a list of items.
+        Root {
+            items: nodes(Item),
+        }
+        /// Syntax which couldn't be parsed.
+        Error {}
+
+        /// The `pub` before an item.
+        Visibility {}
+        /// A module, such as `mod a;` or `mod a { ... }`.
+        Mod {
+            visibility: node(Visibility),
+            name: token(ident),
+            items: node(ItemList),
+        }
+        /// The items of an inline module, such as `{ fn a() = 1; }`.
+        ItemList {
+            items: nodes(Item),
+        }
+        /// An import, such as `use std.prelude.*;`.
+        Use {
+            visibility: node(Visibility),
+            tree: node(UseTree),
+        }
+        /// The imported names of a `use`, such as `std.prelude.[Optional, map]`.
+        UseTree {
+            path: node(Path),
+            glob: token(*),
+            list: node(UseTreeList),
+        }
+        /// A list of imported names, such as `[Optional, map]`.
+        UseTreeList {
+            trees: nodes(UseTree),
+        }
+        /// A path of names separated by `.`, such as `std.prelude`.
+        Path {
+            segments: tokens(ident),
+        }
+        /// A function, such as `fn add(a: Int, b: Int) -> Int = a + b;`, or a
+        /// method of a trait, whose body is optional.
+        Fn {
+            visibility: node(Visibility),
+            name: token(ident),
+            params: node(ParamList),
+            return_type: node(TypeExpr),
+            with_clause: node(WithClause),
+            body: node(Expr),
+        }
+        /// The parameters of a function, such as `(a: Int, b: Int)`.
+        ParamList {
+            params: nodes(Param),
+        }
+        /// A parameter of a function, such as `a: Int`.
+        Param {
+            name: token(ident),
+            ty: node(TypeExpr),
+        }
+        /// A type definition, which is either a sum of variants, such as
+        /// `type Optional(T) = Some(T) | None;`, or an alias, such as
+        /// `type Name = String;`.
+        Type {
+            visibility: node(Visibility),
+            name: token(ident),
+            generics: node(GenericParamList),
+            sum: node(TypeSum),
+            alias: node(TypeExpr),
+        }
+        /// The type parameters of a definition, such as `(T, U = T)`.
+        GenericParamList {
+            params: nodes(GenericParam),
+        }
+        /// A type parameter, such as `U = T`.
+        GenericParam {
+            name: token(ident),
+            default: node(TypeExpr),
+        }
+        /// The variants of a sum type, such as `Some(T) | None`.
+        TypeSum {
+            variants: nodes(Variant),
+        }
+        /// A variant of a sum type, such as `Some(T)`.
+        Variant {
+            name: token(ident),
+            payload: node(VariantPayload),
+        }
+        /// The types carried by a variant, such as `(T)`.
+        VariantPayload {
+            types: nodes(TypeExpr),
+        }
+        /// A trait, such as `trait Equals(Right) = [fn equals(...) -> Boolean];`.
+        Trait {
+            visibility: node(Visibility),
+            name: token(ident),
+            generics: node(GenericParamList),
+            with_clause: node(WithClause),
+            members: node(TraitMemberList),
+        }
+        /// The methods of a trait.
+        TraitMemberList {
+            methods: nodes(Fn),
+        }
+        /// The bounds of a definition, such as `with [T, Self: Equals]`.
+        WithClause {
+            bounds: node(BoundList),
+            bound: node(Bound),
+        }
+        /// A list of bounds, such as `[T, Self: Equals]`.
+        BoundList {
+            bounds: nodes(Bound),
+        }
+        /// A bound, which either introduces a type parameter, as in `T`, or
+        /// requires a type to implement a trait, as in `Self: Equals`.
+        Bound {
+            ty: before(:, 0, TypeExpr),
+            trait_: after(:, TypeExpr),
+        }
+
+        /// A named type, such as `Int` or `std.String`.
+        TypePath {
+            path: node(Path),
+        }
+        /// A type applied to arguments, such as `Optional(Int)`.
+        TypeApplied {
+            path: node(Path),
+            args: node(TypeArgList),
+        }
+        /// The arguments of a type, such as `(Int, String)`.
+        TypeArgList {
+            types: nodes(TypeExpr),
+        }
+        /// A function type, such as `Fn(Int) -> Int`.
+        TypeFn {
+            params: node(TypeArgList),
+            return_type: node(TypeExpr),
+        }
+        /// A list type, such as `List(Int)`.
+        TypeList {
+            args: node(TypeArgList),
+        }
+        /// A tuple type, such as `(Int, String)`.
+        TypeTuple {
+            types: nodes(TypeExpr),
+        }
+        /// The unit type, `()`.
+        TypeUnit {}
+        /// A type in parentheses, such as `(Int)`.
+        TypeParen {
+            ty: node(TypeExpr),
+        }
+        /// A record type, such as `{ x: Int, y: Int }`.
+        TypeRecord {
+            fields: nodes(TypeRecordField),
+        }
+        /// A field of a record type, such as `x: Int`.
+        TypeRecordField {
+            name: token(ident),
+            ty: node(TypeExpr),
+        }
+
+        /// A literal, such as `1`, `"a"` or `true`.
+        ExprLiteral {
+            literal: token(int, float, bool, string, raw_string, char),
+        }
+        /// A reference to a name, such as `a`.
+        ExprName {
+            name: token(ident),
+        }
+        /// A block, such as `{ let a = 1; a + 1 }`.
+        ExprBlock {
+            stmts: nodes(Stmt),
+            tail: node(Expr),
+        }
+        /// A `let` statement, such as `let a: Int = 1;`.
+        StmtLet {
+            pat: node(Pat),
+            ty: node(TypeExpr),
+            value: node(Expr),
+        }
+        /// An expression followed by `;`.
+        StmtExpr {
+            expr: node(Expr),
+        }
+        /// A prefix operator applied to an expression, such as `-a`.
+        ExprUnary {
+            operator: token(-, ~),
+            operand: node(Expr),
+        }
+        /// A binary operator applied to two expressions, such as `a + b`.
+        ExprBinary {
+            lhs: nth(0, Expr),
+            operator: token(|, &, ==, !=, <, <=, >, >=, +, -, *, /),
+            rhs: nth(1, Expr),
+        }
+        /// A function call, such as `f(a, b)`.
+        ExprCall {
+            callee: node(Expr),
+            args: node(ArgList),
+        }
+        /// The arguments of a call, such as `(a, b)`.
+        ArgList {
+            args: nodes(Arg),
+        }
+        /// An argument of a call.
+        Arg {
+            expr: node(Expr),
+        }
+        /// A field access, such as `a.b`.
+        ExprField {
+            expr: node(Expr),
+            field: token(ident),
+        }
+        /// An index into a list, such as `a[0]`.
+        ExprIndex {
+            expr: nth(0, Expr),
+            index: nth(1, Expr),
+        }
+        /// A list, such as `[1, 2, 3]`.
+        ExprList {
+            elements: nodes(Expr),
+        }
+        /// A record, such as `Point { x: 1, y: 2 }`.
+        ExprRecord {
+            name: node(Expr),
+            fields: node(RecordFieldList),
+        }
+        /// The fields of a record, such as `{ x: 1, y: 2 }`.
+        RecordFieldList {
+            fields: nodes(RecordField),
+        }
+        /// A field of a record, such as `x: 1`.
+        RecordField {
+            name: token(ident),
+            value: node(Expr),
+        }
+        /// An `if` expression, such as `if a { 1 } else { 2 }`, where the
+        /// `else` branch is either a block or another `if`.
+        ExprIf {
+            condition: before(else, 0, Expr),
+            then_branch: before(else, 1, ExprBlock),
+            else_branch: after(else, Expr),
+        }
+        /// A lambda, such as `|a, b: Int| a + b`.
+        ExprLambda {
+            params: node(LambdaParamList),
+            body: node(Expr),
+        }
+        /// The parameters of a lambda, such as `|a, b: Int|`.
+        LambdaParamList {
+            params: nodes(LambdaParam),
+        }
+        /// A parameter of a lambda, whose type is optional.
+        LambdaParam {
+            name: token(ident),
+            ty: node(TypeExpr),
+        }
+        /// A `match` expression, such as `match a { Some(b) => b, None => 0 }`.
+        ExprMatch {
+            scrutinee: node(Expr),
+            arms: node(MatchArmList),
+        }
+        /// The arms of a `match`.
+        MatchArmList {
+            arms: nodes(MatchArm),
+        }
+        /// An arm of a `match`, such as `Some(b) => b`.
+        MatchArm {
+            pat: node(Pat),
+            body: node(Expr),
+        }
+
+        /// The pattern `_`.
+        PatWildcard {}
+        /// A name, which either binds a variable or refers to a variant without
+        /// a payload, such as `None`.
+        PatBinding {
+            name: token(ident),
+        }
+        /// A literal pattern, such as `1`, `-1` or `"a"`.
+        PatLiteral {
+            minus: token(-),
+            literal: token(int, float, bool, string, raw_string, char),
+        }
+        /// A variant with a payload, such as `Some(a)`.
+        PatConstructor {
+            name: token(ident),
+            args: node(PatArgList),
+        }
+        /// The payload patterns of a variant, such as `(a)`.
+        PatArgList {
+            pats: nodes(Pat),
+        }
+        /// Alternative patterns, such as `1 | 2`.
+        PatOr {
+            pats: nodes(Pat),
+        }
+    }
+
+    enums {
+        /// An item, at the top level of a file or in a module.
+        Item {
+            Fn(Fn),
+            Type(Type),
+            Trait(Trait),
+            Mod(Mod),
+            Use(Use),
+        }
+        /// A type, as written in a signature or definition.
+        TypeExpr {
+            Path(TypePath),
+            Applied(TypeApplied),
+            Fn(TypeFn),
+            List(TypeList),
+            Tuple(TypeTuple),
+            Unit(TypeUnit),
+            Paren(TypeParen),
+            Record(TypeRecord),
+        }
+        /// An expression.
+        Expr {
+            Literal(ExprLiteral),
+            Name(ExprName),
+            Block(ExprBlock),
+            Unary(ExprUnary),
+            Binary(ExprBinary),
+            Call(ExprCall),
+            Field(ExprField),
+            Index(ExprIndex),
+            List(ExprList),
+            Record(ExprRecord),
+            If(ExprIf),
+            Lambda(ExprLambda),
+            Match(ExprMatch),
+        }
+        /// A statement in a block, other than its final expression.
+        Stmt {
+            Let(StmtLet),
+            Expr(StmtExpr),
+        }
+        /// A pattern, in a `match` arm or a `let` statement.
+        Pat {
+            Wildcard(PatWildcard),
+            Binding(PatBinding),
+            Literal(PatLiteral),
+            Constructor(PatConstructor),
+            Or(PatOr),
+        }
+    }
+}
+
+impl ExprLiteral {
+    /// The value of an integer or float literal.
+    pub(crate) fn number(&self) -> Option<Symbol> {
+        number(&self.literal()?)
+    }
+}
+
+impl PatLiteral {
+    /// The value of an integer or float pattern, with its `-` applied.
+    pub(crate) fn number(&self) -> Option<Symbol> {
+        let value = number(&self.literal()?)?;
+        Some(if self.minus().is_some() { super::literal::negate(value) } else { value })
+    }
+}
+
+/// Decode a numeric literal, or return `None` for any other token or one which
+/// is malformed.
+fn number(token: &Token) -> Option<Symbol> {
+    match token.kind() {
+        t![int] => super::literal::int(&token.text()),
+        t![float] => super::literal::float(&token.text()),
+        _ => None,
+    }
+}
+
+macro_rules! ast {
+    (
+        nodes {$(
+            $(#[$node_meta:meta])*
+            $node:ident { $($accessor:ident: $form:ident($($arg:tt)*),)* }
+        )*}
+        enums {$(
+            $(#[$enum_meta:meta])*
+            $enum:ident { $($variant:ident($variant_node:ident),)* }
+        )*}
+    ) => {
+        $(
+            $(#[$node_meta])*
+            #[derive(::core::clone::Clone)]
+            pub(crate) struct $node {
+                node: Node,
+            }
+
+            impl AstNode for $node {
+                fn cast(node: Node) -> Option<Self> {
+                    (node.kind() == Kind::$node).then_some(Self { node })
+                }
+
+                fn node(&self) -> &Node {
+                    &self.node
+                }
+            }
+
+            impl $node {
+                pub(crate) fn span(&self) -> Span {
+                    self.node.span()
+                }
+
+                $(ast!(@accessor $accessor $form($($arg)*));)*
+            }
+        )*
+
+        $(
+            $(#[$enum_meta])*
+            #[derive(::core::clone::Clone)]
+            pub(crate) enum $enum {
+                $($variant($variant_node),)*
+            }
+
+            impl AstNode for $enum {
+                fn cast(node: Node) -> Option<Self> {
+                    match node.kind() {
+                        $(Kind::$variant_node => Some(Self::$variant($variant_node { node })),)*
+                        _ => None,
+                    }
+                }
+
+                fn node(&self) -> &Node {
+                    match self {
+                        $(Self::$variant(node) => &node.node,)*
+                    }
+                }
+            }
+        )*
+
+        /// Every kind of node which has a typed view.
+        #[cfg(test)]
+        const KINDS: &[Kind] = &[$(Kind::$node),*];
+    };
+    (@accessor $name:ident node($ty:ident)) => {
+        pub(crate) fn $name(&self) -> Option<$ty> {
+            children(&self.node).next()
+        }
+    };
+    (@accessor $name:ident nodes($ty:ident)) => {
+        pub(crate) fn $name(&self) -> impl Iterator<Item = $ty> + use<> {
+            children(&self.node)
+        }
+    };
+    (@accessor $name:ident nth($n:literal, $ty:ident)) => {
+        pub(crate) fn $name(&self) -> Option<$ty> {
+            nth_child(&self.node, $n)
+        }
+    };
+    (@accessor $name:ident before($token:tt, $n:literal, $ty:ident)) => {
+        pub(crate) fn $name(&self) -> Option<$ty> {
+            nth_child_before(&self.node, t![$token], $n)
+        }
+    };
+    (@accessor $name:ident after($token:tt, $ty:ident)) => {
+        pub(crate) fn $name(&self) -> Option<$ty> {
+            child_after(&self.node, t![$token])
+        }
+    };
+    (@accessor $name:ident token($($kind:tt),+)) => {
+        pub(crate) fn $name(&self) -> Option<Token> {
+            tokens(&self.node, &[$(t![$kind]),+]).next()
+        }
+    };
+    (@accessor $name:ident tokens($($kind:tt),+)) => {
+        pub(crate) fn $name(&self) -> impl Iterator<Item = Token> + use<> {
+            tokens(&self.node, &[$(t![$kind]),+])
+        }
+    };
+}
+use ast;
+
+#[cfg(test)]
+mod tests {
+    use super::{AstNode, Error, Expr, Item, Pat, Root, TypeExpr};
+    use crate::syntax::{kind::Kind, test_tree};
+
+    /// The first item of `text`, which must be a function.
+    fn first_fn(text: &str) -> super::Fn {
+        let root = Root::cast(test_tree(text).root()).unwrap();
+        let Some(Item::Fn(f)) = root.items().next() else { panic!("expected a function") };
+        f
+    }
+
+    #[test]
+    fn every_node_kind_has_a_view() {
+        for kind in Kind::NODES {
+            assert!(super::KINDS.contains(kind), "no typed view for {kind:?}");
+        }
+    }
+
+    #[test]
+    fn fn_parts() {
+        let text = "pub fn add(a: Int, b: Int) -> Int = a + b;";
+        let f = first_fn(text);
+
+        assert!(f.visibility().is_some());
+        assert_eq!(&text[f.name().unwrap().span()], "add");
+        let params = f.params().unwrap().params().collect::<Vec<_>>();
+        assert_eq!(params.len(), 2);
+        assert_eq!(&text[params[1].name().unwrap().span()], "b");
+        assert!(matches!(params[1].ty(), Some(TypeExpr::Path(_))));
+        assert!(matches!(f.return_type(), Some(TypeExpr::Path(_))));
+        assert!(f.with_clause().is_none());
+
+        let Some(Expr::Binary(body)) = f.body() else { panic!("expected a binary expression") };
+        assert_eq!(body.operator().unwrap().kind(), Kind::Plus);
+        let Some(Expr::Name(rhs)) = body.rhs() else { panic!("expected a name") };
+        assert_eq!(&text[rhs.name().unwrap().span()], "b");
+    }
+
+    #[test]
+    fn missing_parts() {
+        let f = first_fn("fn foo(a: ");
+
+        let param = f.params().unwrap().params().next().unwrap();
+        assert!(param.name().is_some());
+        assert!(param.ty().is_none());
+        assert!(f.return_type().is_none());
+        assert!(f.body().is_none());
+    }
+
+    #[test]
+    fn error_parts() {
+        let text = "fn foo() = + 1;";
+        let f = first_fn(text);
+
+        assert!(f.body().is_none());
+        let error = f.node().children().find_map(Error::cast).unwrap();
+        assert_eq!(&text[error.span()], "+ 1");
+    }
+
+    #[test]
+    fn literals() {
+        let text = "fn foo() = [1, \"a\", 2.5];";
+        let f = first_fn(text);
+        let Some(Expr::List(list)) = f.body() else { panic!("expected a list") };
+
+        let kinds = list
+            .elements()
+            .map(|element| match element {
+                Expr::Literal(literal) => literal.literal().unwrap().kind(),
+                _ => panic!("expected a literal"),
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(kinds, [Kind::IntLiteral, Kind::StringLiteral, Kind::FloatLiteral]);
+    }
+
+    #[test]
+    fn literal_numbers() {
+        let f = first_fn("fn foo() = match 0x10 { -2.5 => 1e400, 3 => 0 };");
+        let Some(Expr::Match(expr_match)) = f.body() else { panic!("expected a match") };
+        let Some(Expr::Literal(scrutinee)) = expr_match.scrutinee() else {
+            panic!("expected a literal")
+        };
+        assert_eq!(scrutinee.number().unwrap().as_str(), "16");
+
+        let arms = expr_match.arms().unwrap().arms().collect::<Vec<_>>();
+        let Some(Pat::Literal(negative)) = arms[0].pat() else { panic!("expected a literal") };
+        assert_eq!(negative.number().unwrap().as_str(), "-2.5");
+        let Some(Expr::Literal(overflow)) = arms[0].body() else { panic!("expected a literal") };
+        assert!(overflow.number().is_none());
+        let Some(Pat::Literal(positive)) = arms[1].pat() else { panic!("expected a literal") };
+        assert_eq!(positive.number().unwrap().as_str(), "3");
+    }
+
+    #[test]
+    fn positional_parts() {
+        let text = "fn foo() = if a { b } else if c { d };";
+        let f = first_fn(text);
+        let Some(Expr::If(expr_if)) = f.body() else { panic!("expected an if") };
+
+        assert!(matches!(expr_if.condition(), Some(Expr::Name(_))));
+        assert!(expr_if.then_branch().unwrap().tail().is_some());
+        let Some(Expr::If(else_if)) = expr_if.else_branch() else { panic!("expected an if") };
+        assert!(else_if.else_branch().is_none());
+        assert_eq!(else_if.node(), expr_if.else_branch().unwrap().node());
+    }
+
+    #[test]
+    fn missing_positional_parts() {
+        let text = "fn foo() = if a else { b };";
+        let f = first_fn(text);
+        let Some(Expr::If(expr_if)) = f.body() else { panic!("expected an if") };
+
+        assert!(matches!(expr_if.condition(), Some(Expr::Name(_))));
+        assert!(expr_if.then_branch().is_none());
+        assert!(matches!(expr_if.else_branch(), Some(Expr::Block(_))));
+
+        let text = "fn foo() with : Equals = a;";
+        let f = first_fn(text);
+        let bound = f.with_clause().unwrap().bound().unwrap();
+
+        assert!(bound.ty().is_none());
+        let Some(TypeExpr::Path(trait_)) = bound.trait_() else { panic!("expected a path") };
+        assert_eq!(&text[trait_.span()], "Equals");
+    }
+}
@@ -1,6 +1,6 @@
 use super::{
     kind::{Kind, t},
-    parser::{Parser, expected},
+    parser::{MarkClosed, MarkOpened, Parser, expected},
 };
 
 #[cfg(test)]
@@ -10,13 +10,7 @@ pub(super) fn root(p: &mut Parser<'_>) {
     let m = p.open();
     p.eat_trivia();
     while !p.at_end() {
-        match p.peek() {
-            t![fn] => fn_(p),
-            found => {
-                expected!(p, found, fn ("an item"));
-                p.bump();
-            }
-        }
+        item(p);
     }
     p.close(m, Kind::Root);
 }
@@ -24,32 +18,272 @@ pub(super) fn root(p: &mut Parser<'_>) {
 test!(empty, "");
 test!(multiple_items, "fn foo() = true;\nfn bar() = false;");
 test!(missing_item, "awawa");
+test!(unknown_character, "fn foo() = 1 § ;");
+test!(unknown_character_run, "fn foo() = 1 $§@ ;");
+test!(unknown_character_leading, "¤ fn foo() = 1;");
+test!(unknown_confusables, "fn foo() = ’a’；");
+test!(unknown_invisible, "fn foo\u{200B}() = 1;");
+test!(reserved_keyword, "fn return() = while;");
+test!(line_comments, "// a\n/// b\n//! c\n//// d\nfn foo() = true; // e");
+test!(block_comments, "/* a /* b */ c */ fn /**/ foo() = true;");
+test!(unterminated_block_comment, "fn foo() = true; /* a /* b */");
+test!(recover_between_items, "fn foo() = 1;\n1 2 + 3\nfn bar() = 2;");
+test!(recover_unmatched_brace, "} fn foo() = 1;");
+test!(recover_item_end, "type A = B C D;\nfn foo() = 1;");
+test!(recover_missing_fn_equals, "fn foo() Int = 1;\nfn bar() = 2;");
+test!(recover_item_list, "mod inner { 1 + 2 fn foo() = 1; }\nfn bar() = 2;");
+test!(recover_expr, "fn foo() = + 1 2;\nfn bar() = 2;");
+test!(recover_expr_nested, "fn foo() = + (a, [b, { c; d }]);\nfn bar() = 2;");
+test!(recover_expr_nested_unclosed, "fn foo() = + (a, [b;\nfn bar() = 2;");
+test!(recover_expr_nested_mismatched, "fn foo() = { + (a, b };\nfn bar() = 2;");
+test!(recover_pattern_nested, "fn foo() = { let + (a, b) = 1; a };");
+test!(recover_statement, "fn foo() = { let a = 1; ) ); a };");
+test!(recover_let_pattern, "fn foo() = { let = 1; 2 };");
+test!(recover_arg_list, "fn foo() = f(a => b, c);\nfn bar() = 2;");
+test!(recover_param_list, "fn foo(a: Int 1 2) = a;");
+test!(recover_end_of_input, "fn foo(");
+
+/// The tokens which can begin an item.
+const ITEM_FIRST: [Kind; 6] = [t![pub], t![fn], t![type], t![trait], t![mod], t![use]];
+
+/// `tokens` followed by [`ITEM_FIRST`], since most mistakes end at the start of
+/// the next item.
+const fn with_item_first<const N: usize, const M: usize>(tokens: [Kind; N]) -> [Kind; M] {
+    assert!(M == N + ITEM_FIRST.len());
+    let mut result = [t![eof]; M];
+    let mut i = 0;
+    while i < N {
+        result[i] = tokens[i];
+        i += 1;
+    }
+    while i < M {
+        result[i] = ITEM_FIRST[i - N];
+        i += 1;
+    }
+    result
+}
+
+#[track_caller]
+fn at_item_start(p: &mut Parser<'_>) -> bool {
+    ITEM_FIRST.contains(&p.peek())
+}
+
+/// The tokens at which parsing resumes after a malformed item: the start of
+/// the next item, or the end of the enclosing item list.
+const ITEM_RECOVERY: [Kind; 7] = with_item_first([t!['}']]);
+
+/// The tokens at which parsing resumes after a malformed part of an item.
+const ITEM_END_RECOVERY: [Kind; 8] = with_item_first([t![;], t!['}']]);
+
+/// The tokens which end a construct, at which parsing resumes after a mistake
+/// inside it.
+const RECOVERY: [Kind; 10] = with_item_first([t![;], t![')'], t![']'], t!['}']]);
+
+fn item(p: &mut Parser<'_>) {
+    if !at_item_start(p) {
+        let m = p.open();
+        let found = p.peek();
+        expected!(p, found, pub, fn, type, trait, mod, use ("an item"));
+        // the first token is always skipped, since a `}` at the top level has
+        // no item list to end
+        p.bump();
+        p.skip_until(&ITEM_RECOVERY);
+        p.close(m, Kind::Error);
+        return;
+    }
+    let m = p.open();
+    if p.peek() == t![pub] {
+        let visibility = p.open();
+        p.bump();
+        p.close(visibility, Kind::Visibility);
+    }
+    match p.peek() {
+        t![fn] => fn_(p, m),
+        t![type] => type_(p, m),
+        t![trait] => trait_(p, m),
+        t![mod] => mod_(p, m),
+        t![use] => use_(p, m),
+        found => {
+            expected!(p, found, fn, type, trait, mod, use ("an item after `pub`"));
+            p.skip_until(&ITEM_RECOVERY);
+            p.close(m, Kind::Error);
+        }
+    }
+}
+
+/// The rule which parses a node of `kind`, whose parent is of kind `parent`,
+/// by itself, for the nodes which can be parsed again after an edit without
+/// parsing their surroundings.
+pub(super) fn reparser(kind: Kind, parent: Kind) -> Option<fn(&mut Parser<'_>)> {
+    match (kind, parent) {
+        // a method in a trait is also an `Fn`, but has a rule of its own
+        (
+            Kind::Fn | Kind::Type | Kind::Trait | Kind::Mod | Kind::Use,
+            Kind::Root | Kind::ItemList,
+        ) => Some(item),
+        (Kind::ExprBlock, _) => Some(|p| {
+            let m = p.open();
+            expr_block(p, m);
+        }),
+        _ => None,
+    }
+}
+
+test!(visibility, "pub fn foo() = true;\npub type T = A | B;\npub trait U = [];\npub use a.b;");
+test!(visibility_without_item, "pub 1; fn foo() = true;");
+
+/// Expect the `;` which ends an item.
+fn item_semi(p: &mut Parser<'_>) {
+    if !p.optional(t![;]) {
+        p.expect(t![;]);
+        item_end_recover(p);
+    }
+}
+
+/// Skip the rest of a malformed item, up to and including its `;`, unless the
+/// next item or the end of the enclosing item list comes first.
+fn item_end_recover(p: &mut Parser<'_>) {
+    p.recover(&ITEM_END_RECOVERY);
+    p.optional(t![;]);
+}
+
+/// Expect the delimiter which closes a list, skipping anything malformed
+/// before it, unless the end of an enclosing construct comes first.
+fn expect_closing(p: &mut Parser<'_>, closing: Kind) {
+    if p.optional(closing) {
+        return;
+    }
+    p.expect(closing);
+    // a stray `;` just before the delimiter, as in `(a: ;)`, doesn't end the
+    // enclosing item
+    if p.peek() == t![;] && p.lookahead().nth(1) == Some(closing) {
+        p.recover(&[closing]);
+        p.bump();
+        return;
+    }
+    if !RECOVERY.contains(&p.peek()) {
+        p.recover(&RECOVERY);
+        p.optional(closing);
+    }
+}
+
+fn mod_(p: &mut Parser<'_>, m: MarkOpened) {
+    p.expect(t![mod]);
+    p.expect(t![ident]);
+    match p.peek() {
+        t![;] => p.bump(),
+        t!['{'] => item_list(p),
+        found => {
+            expected!(p, found, ;, '{');
+            item_end_recover(p);
+        }
+    }
+    p.close(m, Kind::Mod);
+}
+
+fn item_list(p: &mut Parser<'_>) {
+    let m = p.open();
+    p.expect(t!['{']);
+    while !matches!(p.peek(), t!['}'] | t![eof]) {
+        item(p);
+    }
+    p.expect(t!['}']);
+    p.close(m, Kind::ItemList);
+}
+
+test!(mod_declaration, "pub mod boolean;\nmod comparison;");
+test!(mod_inline, "mod inner {\n    pub fn foo() = true;\n    mod nested {}\n}");
+test!(mod_unclosed, "mod inner { fn foo() = true;");
+test!(mod_missing_body, "mod inner fn foo() = true;");
+
+fn use_(p: &mut Parser<'_>, m: MarkOpened) {
+    p.expect(t![use]);
+    use_tree(p);
+    item_semi(p);
+    p.close(m, Kind::Use);
+}
+
+fn use_tree(p: &mut Parser<'_>) {
+    let m = p.open();
+    path(p);
+    if p.optional(t![.]) {
+        match p.peek() {
+            t![*] => p.bump(),
+            t!['['] => use_tree_list(p),
+            found => expected!(p, found, ident, *, '['),
+        }
+    }
+    p.close(m, Kind::UseTree);
+}
+
+fn use_tree_list(p: &mut Parser<'_>) {
+    let m = p.open();
+    p.expect(t!['[']);
+    while p.peek() == t![ident] {
+        use_tree(p);
+        if matches!(p.peek(), t![,] | t![ident]) {
+            p.expect(t![,]);
+        }
+    }
+    expect_closing(p, t![']']);
+    p.close(m, Kind::UseTreeList);
+}
 
-fn fn_(p: &mut Parser<'_>) {
+/// A path of names separated by `.`, stopping before a `.` which is not
+/// followed by another name, as in `use std.prelude.*`.
+fn path(p: &mut Parser<'_>) {
     let m = p.open();
+    p.expect(t![ident]);
+    while p.peek() == t![.] && p.lookahead().nth(1) == Some(t![ident]) {
+        p.bump();
+        p.bump();
+    }
+    p.close(m, Kind::Path);
+}
+
+test!(use_path, "use std.prelude.Optional;");
+test!(use_glob, "use std.prelude.*;");
+test!(use_nested, "use lib.[boolean.Boolean, Comparison.[Less, Equal, Greater]];");
+test!(use_missing_comma, "use lib.[a b, c];");
+test!(use_trailing_dot, "use lib.;");
+
+fn fn_(p: &mut Parser<'_>, m: MarkOpened) {
+    fn_signature(p);
+    if p.optional(t![=]) {
+        expr(p);
+        item_semi(p);
+    } else {
+        // without the `=`, whatever follows is unlikely to be the body
+        p.expect(t![=]);
+        item_end_recover(p);
+    }
+    p.close(m, Kind::Fn);
+}
+
+fn fn_signature(p: &mut Parser<'_>) {
     p.expect(t![fn]);
     p.expect(t![ident]);
     param_list(p);
     if p.optional(t![->]) {
         type_expr(p);
     }
-    p.expect(t![=]);
-    expr(p);
-    p.expect(t![;]);
-    p.close(m, Kind::Fn);
+    if p.peek() == t![with] {
+        with_clause(p);
+    }
 }
 
 test!(fn_minimal, "fn foo() = true;");
 test!(fn_params, "fn foo(x: Bool) = true;");
 test!(fn_return_type, "fn foo() -> Bool = true;");
+test!(fn_with_clause, "fn id(x: A) -> A with [A] = x;");
 
 fn param_list(p: &mut Parser<'_>) {
     let m = p.open();
     p.expect(t!['(']);
-    while p.peek() != t![')'] && !p.at_end() {
+    while p.peek() == t![ident] {
         param(p);
     }
-    p.expect(t![')']);
+    expect_closing(p, t![')']);
     p.close(m, Kind::ParamList);
 }
 
@@ -58,7 +292,7 @@ fn param(p: &mut Parser<'_>) {
     p.expect(t![ident]);
     p.expect(t![:]);
     type_expr(p);
-    if p.peek() != t![')'] {
+    if matches!(p.peek(), t![,] | t![ident]) {
         p.expect(t![,]);
     }
     p.close(m, Kind::Param);
@@ -69,55 +303,1008 @@ test!(params_no_trailing, "fn foo(x: Int, y: Bool) = true;");
 test!(params_with_trailing, "fn foo(x: Int, y: Bool,) = true;");
 test!(params_missing_recovery, "fn foo(x: , y Bool) = true;");
 
+fn type_(p: &mut Parser<'_>, m: MarkOpened) {
+    p.expect(t![type]);
+    p.expect(t![ident]);
+    if p.peek() == t!['('] {
+        generic_param_list(p);
+    }
+    p.expect(t![=]);
+    if at_type_sum(p) {
+        type_sum(p);
+    } else {
+        type_expr(p);
+    }
+    item_semi(p);
+    p.close(m, Kind::Type);
+}
+
+/// Whether the body of a `type` item is a sum of variants, rather than an
+/// alias for another type. A variant such as `Some(A)` looks just like a type,
+/// so a sum is recognised by a `|` before the end of the item, and a sum with a
+/// single variant is written with a leading `|`.
+fn at_type_sum(p: &mut Parser<'_>) -> bool {
+    p.lookahead()
+        .take_while(|&kind| kind != t![;] && !ITEM_FIRST.contains(&kind))
+        .any(|kind| kind == t![|])
+}
+
+test!(type_sum, "type Comparison = Less | Equal | Greater;");
+test!(type_sum_generic, "type Optional(A) = Some(A) | None;");
+test!(type_sum_payloads, "type Either(A, B,) = Left(A) | Right(B) | Both(A, B,);");
+test!(type_sum_leading_pipe, "type Wrapper(A) = | Wrapper(A);");
+test!(type_alias, "type Flag = Boolean;");
+test!(type_missing_variant, "type Comparison = Less | | Greater;");
+test!(type_missing_semi, "type Flag = Boolean\nfn foo() = true;");
+test!(type_missing_semi_sum, "type Flag = On | Off\nfn foo() = a | b;");
+
+fn generic_param_list(p: &mut Parser<'_>) {
+    let m = p.open();
+    p.expect(t!['(']);
+    while p.peek() == t![ident] {
+        generic_param(p);
+    }
+    expect_closing(p, t![')']);
+    p.close(m, Kind::GenericParamList);
+}
+
+fn generic_param(p: &mut Parser<'_>) {
+    let m = p.open();
+    p.expect(t![ident]);
+    if p.optional(t![=]) {
+        type_expr(p);
+    }
+    if matches!(p.peek(), t![,] | t![ident]) {
+        p.expect(t![,]);
+    }
+    p.close(m, Kind::GenericParam);
+}
+
+fn type_sum(p: &mut Parser<'_>) {
+    let m = p.open();
+    p.optional(t![|]);
+    variant(p);
+    while p.optional(t![|]) {
+        variant(p);
+    }
+    p.close(m, Kind::TypeSum);
+}
+
+fn variant(p: &mut Parser<'_>) {
+    let m = p.open();
+    p.expect(t![ident]);
+    if p.peek() == t!['('] {
+        variant_payload(p);
+    }
+    p.close(m, Kind::Variant);
+}
+
+fn variant_payload(p: &mut Parser<'_>) {
+    let m = p.open();
+    p.expect(t!['(']);
+    while p.peek() != t![')'] && at_type_start(p) {
+        type_expr(p);
+        if p.peek() != t![')'] && (p.peek() == t![,] || at_type_start(p)) {
+            p.expect(t![,]);
+        }
+    }
+    expect_closing(p, t![')']);
+    p.close(m, Kind::VariantPayload);
+}
+
+fn trait_(p: &mut Parser<'_>, m: MarkOpened) {
+    p.expect(t![trait]);
+    p.expect(t![ident]);
+    if p.peek() == t!['('] {
+        generic_param_list(p);
+    }
+    if p.peek() == t![with] {
+        with_clause(p);
+    }
+    p.expect(t![=]);
+    trait_member_list(p);
+    item_semi(p);
+    p.close(m, Kind::Trait);
+}
+
+fn trait_member_list(p: &mut Parser<'_>) {
+    let m = p.open();
+    p.expect(t!['[']);
+    while !matches!(p.peek(), t![']'] | t![;] | t![eof])
+        && (p.peek() == t![fn] || !at_item_start(p))
+    {
+        if p.peek() == t![fn] {
+            trait_method(p);
+        } else {
+            trait_member_error(p);
+        }
+    }
+    p.expect(t![']']);
+    p.close(m, Kind::TraitMemberList);
+}
+
+/// A method of a trait, which is a function whose body is optional, serving as
+/// a default for implementations.
+fn trait_method(p: &mut Parser<'_>) {
+    let m = p.open();
+    fn_signature(p);
+    if p.optional(t![=]) {
+        expr(p);
+    }
+    if matches!(p.peek(), t![,] | t![fn]) {
+        p.expect(t![,]);
+    }
+    p.close(m, Kind::Fn);
+}
+
+/// The tokens at which parsing resumes after a malformed trait member.
+const TRAIT_MEMBER_RECOVERY: [Kind; 9] = with_item_first([t![,], t![']'], t![;]]);
+
+/// Skip a malformed trait member up to the start of the next one, so that the
+/// rest of the trait is still parsed.
+fn trait_member_error(p: &mut Parser<'_>) {
+    let m = p.open();
+    let found = p.peek();
+    expected!(p, found, fn ("a trait method"));
+    p.skip_until(&TRAIT_MEMBER_RECOVERY);
+    p.optional(t![,]);
+    p.close(m, Kind::Error);
+}
+
+test!(
+    trait_std_equals,
+    "trait Equals(Right) with Right = [
+    fn equals(left: Self, right: Right) -> Boolean,
+    fn not_equals(left: Self, right: Right) -> Boolean = ~equals(left, right),
+];"
+);
+test!(
+    trait_bounds,
+    "trait Compare(Right) with [Right, Self: Equals,] = [
+    fn compare(left: Self, right: Right) -> Comparison,
+    fn less(left: Self, right: Right) -> Boolean = compare(left, right) == Less,
+];"
+);
+test!(
+    trait_generic_default,
+    "trait Add(Right = Self) = [fn add(left: Self, right: Right) -> Self];"
+);
+test!(trait_empty, "trait Marker = [];");
+test!(trait_malformed_member, "trait T = [fn a() -> , 1 + 2, fn b() -> B = c];");
+test!(
+    trait_malformed_member_nested,
+    "trait T = [fun equals(left: Self, right: Right) -> Boolean, fn b() -> List(B) = f([1, 2])];"
+);
+test!(trait_missing_comma, "trait T = [fn a() -> A fn b() -> B];");
+test!(trait_unclosed, "trait T = [fn a() -> A;\ntype U = V;");
+
+fn with_clause(p: &mut Parser<'_>) {
+    let m = p.open();
+    p.expect(t![with]);
+    if p.peek() == t!['['] {
+        bound_list(p);
+    } else {
+        bound(p);
+    }
+    p.close(m, Kind::WithClause);
+}
+
+fn bound_list(p: &mut Parser<'_>) {
+    let m = p.open();
+    p.expect(t!['[']);
+    while p.peek() != t![']'] && at_type_start(p) {
+        bound(p);
+        if p.peek() != t![']'] && (p.peek() == t![,] || at_type_start(p)) {
+            p.expect(t![,]);
+        }
+    }
+    expect_closing(p, t![']']);
+    p.close(m, Kind::BoundList);
+}
+
+/// A bound in a `with` clause, which either introduces a type parameter, as in
+/// `with [A]`, or requires a type to implement a trait, as in `Self: Equals`.
+fn bound(p: &mut Parser<'_>) {
+    let m = p.open();
+    type_expr(p);
+    if p.optional(t![:]) {
+        type_expr(p);
+    }
+    p.close(m, Kind::Bound);
+}
+
+/// The tokens which can begin a type.
+const TYPE_FIRST: &[Kind] = &[t![ident], t!['('], t!['{']];
+
+#[track_caller]
+fn at_type_start(p: &mut Parser<'_>) -> bool {
+    TYPE_FIRST.contains(&p.peek())
+}
+
 fn type_expr(p: &mut Parser<'_>) {
+    if !at_type_start(p) {
+        let found = p.peek();
+        expected!(p, found, ident, '(', '{'("a type"));
+        return;
+    }
+    let m = p.open();
+    if p.peek() == t!['('] {
+        type_delimited(p, m);
+    } else if p.peek() == t!['{'] {
+        type_record(p, m);
+    } else if p.at_contextual_keyword("Fn") && p.lookahead().nth(1) == Some(t!['(']) {
+        p.bump();
+        type_arg_list(p);
+        if p.optional(t![->]) {
+            type_expr(p);
+        } else {
+            let found = p.peek();
+            expected!(p, found, ->);
+        }
+        p.close(m, Kind::TypeFn);
+    } else if p.at_contextual_keyword("List") && p.lookahead().nth(1) == Some(t!['(']) {
+        p.bump();
+        type_arg_list(p);
+        p.close(m, Kind::TypeList);
+    } else {
+        path(p);
+        if p.peek() == t!['('] {
+            type_arg_list(p);
+            p.close(m, Kind::TypeApplied);
+        } else {
+            p.close(m, Kind::TypePath);
+        }
+    }
+}
+
+/// Parse the unit type `()`, a tuple type such as `(A, B)` or `(A,)`, or a
+/// type in parentheses.
+fn type_delimited(p: &mut Parser<'_>, m: MarkOpened) {
+    p.expect(t!['(']);
+    if p.optional(t![')']) {
+        p.close(m, Kind::TypeUnit);
+        return;
+    }
+    type_expr(p);
+    let mut tuple = false;
+    while p.optional(t![,]) {
+        tuple = true;
+        if !at_type_start(p) {
+            break;
+        }
+        type_expr(p);
+    }
+    expect_closing(p, t![')']);
+    p.close(m, if tuple { Kind::TypeTuple } else { Kind::TypeParen });
+}
+
+fn type_record(p: &mut Parser<'_>, m: MarkOpened) {
+    p.expect(t!['{']);
+    while p.peek() == t![ident] {
+        type_record_field(p);
+    }
+    expect_closing(p, t!['}']);
+    p.close(m, Kind::TypeRecord);
+}
+
+fn type_record_field(p: &mut Parser<'_>) {
     let m = p.open();
     p.expect(t![ident]);
-    p.close(m, Kind::TypeExpr);
+    p.expect(t![:]);
+    type_expr(p);
+    if matches!(p.peek(), t![,] | t![ident]) {
+        p.expect(t![,]);
+    }
+    p.close(m, Kind::TypeRecordField);
+}
+
+fn type_arg_list(p: &mut Parser<'_>) {
+    let m = p.open();
+    p.expect(t!['(']);
+    while p.peek() != t![')'] && at_type_start(p) {
+        type_expr(p);
+        if p.peek() != t![')'] && (p.peek() == t![,] || at_type_start(p)) {
+            p.expect(t![,]);
+        }
+    }
+    expect_closing(p, t![')']);
+    p.close(m, Kind::TypeArgList);
 }
 
-fn expr(p: &mut Parser<'_>) {
-    expr_delimited(p);
+test!(type_path, "fn foo(a: comparison.Comparison, b: Self) = a;");
+test!(type_applied, "fn foo(o: Optional(A)) -> Result(Optional(A), std.Error,) = o;");
+test!(type_fn, "fn map(o: Optional(A), f: Fn(A) -> B) -> Optional(B) with [A, B] = o;");
+test!(type_fn_returning_fn, "fn curry(f: Fn(A, B) -> C) -> Fn(A) -> Fn(B) -> C = f;");
+test!(type_fn_missing_return, "fn foo(f: Fn(A)) = f;");
+test!(type_fn_name, "fn foo(f: Fn) = f;");
+test!(type_unit, "fn foo() -> () = {};");
+test!(type_tuple, "fn foo(pair: (A, B), single: (A,)) -> (A, (B, C)) = pair;");
+test!(type_paren, "fn foo(f: (Fn(A) -> B)) = f;");
+test!(type_list, "fn foo(xs: List(List(A))) -> List(A) = xs;");
+test!(type_list_name, "fn foo(xs: List) = xs;");
+test!(type_record, "type Point = { x: Int, y: Int };");
+test!(type_record_empty, "type Unit = {};");
+test!(type_record_nested, "fn foo(p: { x: Int, inner: { y: Int, } }) = p;");
+test!(type_record_missing_colon, "type Point = { x Int, y: Int };");
+test!(type_missing, "fn foo(a: , b: ;) = a;");
+
+fn expr(p: &mut Parser<'_>) -> Option<MarkClosed> {
+    expr_binding(p, 0, true)
+}
+
+/// Parse an expression which is followed by a block, such as the condition of
+/// an `if`, where a record literal would be ambiguous with the block.
+fn expr_no_record(p: &mut Parser<'_>) -> Option<MarkClosed> {
+    expr_binding(p, 0, false)
 }
 
-fn expr_delimited(p: &mut Parser<'_>) {
+/// The binding power of prefix operators, which is higher than that of any
+/// binary operator.
+const PREFIX_BINDING_POWER: u8 = 11;
+
+/// The left and right binding powers of a binary operator. A left-associative
+/// operator binds more tightly on its right.
+fn binary_binding_power(kind: Kind) -> Option<(u8, u8)> {
+    match kind {
+        t![|] => Some((1, 2)),
+        t![&] => Some((3, 4)),
+        t![==] | t![!=] | t![<] | t![<=] | t![>] | t![>=] => Some((5, 6)),
+        t![+] | t![-] => Some((7, 8)),
+        t![*] | t![/] => Some((9, 10)),
+        _ => None,
+    }
+}
+
+fn is_comparison(kind: Kind) -> bool {
+    matches!(kind, t![==] | t![!=] | t![<] | t![<=] | t![>] | t![>=])
+}
+
+fn expr_binding(p: &mut Parser<'_>, min_binding_power: u8, records: bool) -> Option<MarkClosed> {
+    let lhs = match p.peek() {
+        t![-] | t![~] => {
+            let m = p.open();
+            p.bump();
+            expr_binding(p, PREFIX_BINDING_POWER, records);
+            p.close(m, Kind::ExprUnary)
+        }
+        t![|] => expr_lambda(p, records),
+        _ => expr_postfix(p, records)?,
+    };
+    Some(expr_binary(p, lhs, min_binding_power, records))
+}
+
+/// Parse the binary operators which follow `lhs`, and their operands.
+fn expr_binary(
+    p: &mut Parser<'_>,
+    mut lhs: MarkClosed,
+    min_binding_power: u8,
+    records: bool,
+) -> MarkClosed {
+    let mut previous_comparison = false;
+    loop {
+        let operator = p.peek();
+        let Some((left, right)) = binary_binding_power(operator) else {
+            break;
+        };
+        if left < min_binding_power {
+            break;
+        }
+        // comparisons are non-associative, but are parsed as if they were
+        // left-associative so that the rest of the expression is still useful
+        if previous_comparison && is_comparison(operator) {
+            let span = p.peek_span();
+            p.diagnostic(errors::chained_comparison(p.file(), span));
+        }
+        previous_comparison = is_comparison(operator);
+        let m = p.open_before(lhs);
+        p.bump();
+        expr_binding(p, right, records);
+        lhs = p.close(m, Kind::ExprBinary);
+    }
+    lhs
+}
+
+test!(unary_negate_name, "fn foo() = -a;");
+test!(unary_negate_twice, "fn foo() = --1;");
+test!(unary_not, "fn foo() = ~a & b;");
+test!(minus_spaced, "fn foo() = a - 1;");
+test!(minus_unspaced, "fn foo() = a-1;");
+test!(binary_precedence, "fn foo() = 1 + 2 * 3;");
+test!(binary_precedence_left, "fn foo() = 1 * 2 + 3;");
+test!(binary_left_associative, "fn foo() = a - b - c;");
+test!(binary_unary_operand, "fn foo() = -a * -b;");
+test!(binary_comparison, "fn foo() = a + 1 == b * 2;");
+test!(binary_boolean, "fn foo() = a < b & c | d;");
+test!(binary_chained_comparison, "fn foo() = a < b < c;");
+test!(binary_chained_comparison_nested, "fn foo() = a == b + 1 != c;");
+test!(binary_missing_rhs, "fn foo() = a + ;");
+
+fn expr_postfix(p: &mut Parser<'_>, records: bool) -> Option<MarkClosed> {
+    // only a name, possibly with fields, can be followed by a record literal
+    let mut path = p.peek() == t![ident];
+    let mut lhs = expr_delimited(p)?;
+    loop {
+        match p.peek() {
+            t!['('] => {
+                let m = p.open_before(lhs);
+                arg_list(p);
+                lhs = p.close(m, Kind::ExprCall);
+                path = false;
+            }
+            t!['['] => {
+                let m = p.open_before(lhs);
+                p.bump();
+                // an empty index is reported once, rather than also as a
+                // missing `]`
+                if p.peek() == t![']'] {
+                    expected_expr(p);
+                } else {
+                    expr(p);
+                }
+                expect_closing(p, t![']']);
+                lhs = p.close(m, Kind::ExprIndex);
+                path = false;
+            }
+            t![.] => {
+                let m = p.open_before(lhs);
+                p.bump();
+                p.expect(t![ident]);
+                lhs = p.close(m, Kind::ExprField);
+            }
+            t!['{'] if records && path => {
+                let m = p.open_before(lhs);
+                record_field_list(p);
+                lhs = p.close(m, Kind::ExprRecord);
+                path = false;
+            }
+            _ => break,
+        }
+    }
+    Some(lhs)
+}
+
+test!(list_index, "fn foo(xs: List(Int)) -> Int = xs[0] + xs[i + 1][j];");
+test!(list_index_postfix, "fn foo() = f(x)[0].y;");
+test!(list_index_missing, "fn foo() = xs[];");
+test!(list_index_after_with_clause, "fn foo(xs: List(A)) -> A with [A] = xs[0];");
+test!(field_access, "fn foo() = p.x + p.inner.y;");
+test!(field_access_call, "fn foo() = f(a).x.y(b);");
+test!(field_access_missing_name, "fn foo() = p.;");
+
+fn arg_list(p: &mut Parser<'_>) {
+    let m = p.open();
+    p.expect(t!['(']);
+    while p.peek() != t![')'] && at_expr_start(p) {
+        arg(p);
+    }
+    expect_closing(p, t![')']);
+    p.close(m, Kind::ArgList);
+}
+
+fn arg(p: &mut Parser<'_>) {
+    let m = p.open();
+    expr(p);
+    // a missing comma is only reported if another argument follows, so that
+    // an unclosed list is reported once, as a missing `)`
+    if p.peek() != t![')'] && (p.peek() == t![,] || at_expr_start(p)) {
+        p.expect(t![,]);
+    }
+    p.close(m, Kind::Arg);
+}
+
+test!(call_empty, "fn foo() = f();");
+test!(call_args, "fn foo() = compare(left, right);");
+test!(call_trailing_comma, "fn foo() = f(a, b,);");
+test!(call_nested, "fn foo() = Some(f(x));");
+test!(call_chained, "fn foo() = f(a)(b);");
+test!(call_precedence, "fn foo() = -f(x) + g(y) * 2;");
+test!(call_missing_comma, "fn foo() = f(a b);");
+test!(call_unclosed, "fn foo() = f(a;");
+
+/// Parse a lambda such as `|x, y: Int| x + y`, whose body extends as far to
+/// the right as possible.
+fn expr_lambda(p: &mut Parser<'_>, records: bool) -> MarkClosed {
+    let m = p.open();
+    // without the closing `|`, whatever follows is unlikely to be the body
+    if lambda_param_list(p) {
+        expr_binding(p, 0, records);
+    }
+    p.close(m, Kind::ExprLambda)
+}
+
+/// Parse the parameters of a lambda, returning whether the closing `|` was
+/// found.
+fn lambda_param_list(p: &mut Parser<'_>) -> bool {
+    let m = p.open();
+    p.expect(t![|]);
+    while !matches!(p.peek(), t![|] | t![;] | t![')'] | t!['}'] | t![']'] | t![eof]) {
+        if p.peek() == t![ident] {
+            lambda_param(p);
+        } else {
+            let error = p.open();
+            let found = p.peek();
+            expected!(p, found, ident("a parameter"));
+            p.skip_until(&[t![ident], t![|], t![;], t![')'], t!['}'], t![']']]);
+            p.close(error, Kind::Error);
+        }
+    }
+    let closed = p.peek() == t![|];
+    p.expect(t![|]);
+    p.close(m, Kind::LambdaParamList);
+    closed
+}
+
+fn lambda_param(p: &mut Parser<'_>) {
+    let m = p.open();
+    p.expect(t![ident]);
+    if p.optional(t![:]) {
+        type_expr(p);
+    }
+    if matches!(p.peek(), t![,] | t![ident]) {
+        p.expect(t![,]);
+    }
+    p.close(m, Kind::LambdaParam);
+}
+
+test!(lambda, "fn foo() = map(o, |x| x + 1);");
+test!(lambda_typed, "fn foo() = |x: Int, y,| x * y;");
+test!(lambda_no_params, "fn foo() = | | 1;");
+test!(lambda_curried, "fn foo() = |a| |b| a + b;");
+test!(lambda_operand, "fn foo() = 1 + |x| x * 2;");
+test!(lambda_block_body, "fn foo() = |x| { let y = x; y };");
+test!(lambda_missing_body, "fn foo() = map(o, |x|);");
+test!(lambda_unclosed_params, "fn foo() = |x: Int x;");
+test!(lambda_invalid_param, "fn foo() = |1| 2;");
+
+fn record_field_list(p: &mut Parser<'_>) {
+    let m = p.open();
+    p.expect(t!['{']);
+    while p.peek() == t![ident] {
+        record_field(p);
+    }
+    expect_closing(p, t!['}']);
+    p.close(m, Kind::RecordFieldList);
+}
+
+fn record_field(p: &mut Parser<'_>) {
+    let m = p.open();
+    p.expect(t![ident]);
+    p.expect(t![:]);
+    expr(p);
+    if matches!(p.peek(), t![,] | t![ident]) {
+        p.expect(t![,]);
+    }
+    p.close(m, Kind::RecordField);
+}
+
+test!(record_literal, "fn foo() = Point { x: 1, y: 2 };");
+test!(record_literal_path, "fn foo() = shapes.Point { x: 1, y: f(a), };");
+test!(record_literal_empty, "fn foo() = Unit {};");
+test!(record_literal_nested, "fn foo() = Line { start: Point { x: 0, y: 0 }, end: p };");
+test!(record_literal_missing_comma, "fn foo() = Point { x: 1 y: 2 };");
+test!(record_literal_condition, "fn foo() = if p == origin { 1 } else { 2 };");
+test!(record_literal_scrutinee, "fn foo() = match p { Point => 1 };");
+test!(record_literal_in_block_condition, "fn foo() = if { p == Point { x: 1 } } { 1 } else { 2 };");
+
+/// The tokens which can begin an expression.
+const EXPR_FIRST: &[Kind] = &[
+    t![-],
+    t![~],
+    t![|],
+    t![int],
+    t![float],
+    t![bool],
+    t![string],
+    t![raw_string],
+    t![char],
+    t![ident],
+    t!['{'],
+    t!['['],
+    t![if],
+    t![match],
+];
+
+#[track_caller]
+fn at_expr_start(p: &mut Parser<'_>) -> bool {
+    EXPR_FIRST.contains(&p.peek())
+}
+
+/// The tokens at which parsing resumes after a malformed expression: those
+/// which can follow an expression, or begin the next statement or item.
+const EXPR_RECOVERY: [Kind; 12] =
+    with_item_first([t![,], t![;], t![')'], t![']'], t!['}'], t![let]]);
+
+fn expected_expr(p: &mut Parser<'_>) {
+    let found = p.peek();
+    p.unexpected(found, EXPR_FIRST, Some("an expression"));
+}
+
+fn expr_delimited(p: &mut Parser<'_>) -> Option<MarkClosed> {
     let m = p.open();
     match p.peek() {
-        t![int] | t![bool] => {
+        t![int] | t![float] | t![bool] | t![string] | t![raw_string] | t![char] => {
             p.bump();
-            p.close(m, Kind::ExprLiteral);
+            Some(p.close(m, Kind::ExprLiteral))
         }
         t![ident] => {
             p.bump();
-            p.close(m, Kind::ExprName);
+            Some(p.close(m, Kind::ExprName))
         }
-        t!['{'] => {
-            p.expect(t!['{']);
-            expr(p);
-            p.expect(t!['}']);
-            p.close(m, Kind::ExprGroup);
+        t!['{'] => Some(expr_block(p, m)),
+        t!['['] => {
+            p.bump();
+            while p.peek() != t![']'] && at_expr_start(p) {
+                expr(p);
+                if p.peek() != t![']'] && (p.peek() == t![,] || at_expr_start(p)) {
+                    p.expect(t![,]);
+                }
+            }
+            expect_closing(p, t![']']);
+            Some(p.close(m, Kind::ExprList))
         }
-        found => {
-            expected!(p, found, int, bool, ident("an expression"));
-            if !p.at_end() {
-                p.bump();
+        t![if] => {
+            let (expr, missing_else) = expr_if(p, m);
+            if let Some(span) = missing_else {
+                p.diagnostic(errors::missing_else(p.file(), span));
             }
+            Some(expr)
+        }
+        t![match] => {
+            p.bump();
+            expr_no_record(p);
+            match_arm_list(p);
+            Some(p.close(m, Kind::ExprMatch))
+        }
+        _ => {
+            expected_expr(p);
+            p.skip_until(&EXPR_RECOVERY);
             p.close(m, Kind::Error);
+            None
         }
     }
 }
 
+test!(list_literal, "fn foo() = [1, 2, 3];");
+test!(list_nested, "fn foo() = [[], [a,], [f(x), -1]];");
+test!(list_missing_comma, "fn foo() = [1 2, 3];");
+test!(list_unclosed, "fn foo() = [1, 2;");
+test!(list_after_with_clause, "fn foo(x: A) -> List(A) with [A] = [x];");
+test!(list_in_trait_member_list, "trait T with [A] = [fn f() -> List(A) = [], fn g() -> A];");
 test!(bool_literal, "fn foo() = true;");
 test!(int_literal, "fn foo() = -123;");
+test!(int_literal_radix, "fn foo() = 0xFF;\nfn bar() = 0o17;\nfn baz() = 0b1010;");
+test!(int_literal_separators, "fn foo() = 1_000_000;");
+test!(int_literal_invalid_digits, "fn foo() = 0b102;\nfn bar() = 0o8;\nfn baz() = 12a3;");
+test!(int_literal_missing_digits, "fn foo() = 0x_;");
+test!(float_literal, "fn foo() = 1.5;\nfn bar() = 2.5e-3;\nfn baz() = 1E10;");
+test!(float_literal_missing_exponent, "fn foo() = 1.0e+;");
+test!(float_literal_out_of_range, "fn foo() = 1e400;");
+test!(float_literal_exponent_letter, "fn foo() = 1else;");
+test!(string_literal, r#"fn foo() = "a\n\t\\\"\u{1F980}";"#);
+test!(string_unterminated, r#"fn foo() = "abc;"#);
+test!(string_bad_escapes, r#"fn foo() = "\q \u{} \u{D800} \u{1234567} \u12 \u{12";"#);
+test!(raw_string_literal, r##"fn foo() = r#"a "quoted" \n"#;"##);
+test!(raw_string_unterminated, r##"fn foo() = r##"abc"#;"##);
+test!(char_literal, r"fn foo() = '\u{41}';");
+test!(char_errors, "fn foo() = '';\nfn bar() = 'ab';\nfn baz() = 'a");
+test!(char_unterminated, "fn foo() = 'a;\nfn bar() = f('b, 1);");
 test!(ident_expr, "fn foo() = bar;");
 test!(expr_group, "fn foo() = { bar };");
 
+fn expr_block(p: &mut Parser<'_>, m: MarkOpened) -> MarkClosed {
+    p.expect(t!['{']);
+    while !matches!(p.peek(), t!['}'] | t![eof]) && !at_item_start(p) {
+        if p.peek() == t![let] {
+            stmt_let(p);
+            continue;
+        }
+        if !at_expr_start(p) {
+            // the rest of the statement, such as an unmatched `)`, has nothing
+            // more to report
+            expected_expr(p);
+            p.recover(&STMT_RECOVERY);
+            p.optional(t![;]);
+            continue;
+        }
+        let expr = if p.peek() == t![if] {
+            // the value of an `if` statement is discarded, so it doesn't need
+            // an `else`
+            let m = p.open();
+            let (expr, missing_else) = expr_if(p, m);
+            if let Some(span) = missing_else
+                && p.peek() != t![;]
+            {
+                p.diagnostic(errors::missing_else(p.file(), span));
+            }
+            expr
+        } else {
+            let Some(expr) = expr(p) else {
+                continue;
+            };
+            expr
+        };
+        // the last expression in a block, without a `;`, is its value
+        if p.peek() == t!['}'] {
+            break;
+        }
+        let m = p.open_before(expr);
+        stmt_semi(p);
+        p.close(m, Kind::StmtExpr);
+    }
+    p.expect(t!['}']);
+    p.close(m, Kind::ExprBlock)
+}
+
+/// The tokens at which parsing resumes after a malformed statement.
+const STMT_RECOVERY: [Kind; 9] = with_item_first([t![;], t!['}'], t![let]]);
+
+fn stmt_let(p: &mut Parser<'_>) {
+    let m = p.open();
+    p.expect(t![let]);
+    pat(p);
+    if p.optional(t![:]) {
+        type_expr(p);
+    }
+    p.expect(t![=]);
+    expr(p);
+    stmt_semi(p);
+    p.close(m, Kind::StmtLet);
+}
+
+fn stmt_semi(p: &mut Parser<'_>) {
+    if !p.optional(t![;]) {
+        let found = p.peek();
+        let previous = p.previous_span();
+        p.diagnostic(errors::missing_semicolon(p.file(), previous, found));
+    }
+}
+
+test!(block_std, "fn foo(a: Int) -> Int = { let x = a + 1; let y: Int = x * 2; y };");
+test!(block_statements_only, "fn foo() = { let x = 1; f(x); };");
+test!(block_empty, "fn foo() = {};");
+test!(block_nested, "fn foo() = { let x = { let y = 1; y }; x };");
+test!(block_let_pattern, "fn foo() = { let Pair(a, _) = p; a };");
+test!(block_missing_semicolon, "fn foo() = { let x = 1\n let y = 2; x + y };");
+test!(block_missing_semicolon_expr, "fn foo() = { f(x) g(x) };");
+test!(block_missing_semicolon_tail, "fn foo() = { let x = 1 };");
+
+/// Parse an `if` expression, returning the span of the `if` keyword of an
+/// `else if` chain which has no final `else`.
+fn expr_if(p: &mut Parser<'_>, m: MarkOpened) -> (MarkClosed, Option<crate::source::Span>) {
+    let span = p.peek_span();
+    p.expect(t![if]);
+    if_condition(p);
+    let has_block = branch_block(p);
+    let missing_else = if p.optional(t![else]) {
+        match p.peek() {
+            t![if] => {
+                let m = p.open();
+                expr_if(p, m).1
+            }
+            t!['{'] => {
+                branch_block(p);
+                None
+            }
+            found => {
+                expected!(p, found, if, '{');
+                None
+            }
+        }
+    } else {
+        // a missing block has already been reported
+        has_block.then_some(span)
+    };
+    (p.close(m, Kind::ExprIf), missing_else)
+}
+
+fn if_condition(p: &mut Parser<'_>) {
+    if p.peek() != t!['('] {
+        expr_no_record(p);
+        return;
+    }
+    // parentheses don't group expressions, but a C-style condition is still
+    // parsed, so that its contents are checked, as is a parenthesized operand
+    // at the start of the condition, so that the operators after it are too
+    let whole = parenthesizes_condition(p);
+    let m = p.open();
+    let span = p.peek_span();
+    p.bump();
+    if whole {
+        p.diagnostic(errors::parenthesized_condition(p.file(), span));
+    } else {
+        p.diagnostic(errors::parenthesized_expr(p.file(), span));
+    }
+    expr(p);
+    expect_closing(p, t![')']);
+    let lhs = p.close(m, Kind::Error);
+    if !whole {
+        expr_binary(p, lhs, 0, false);
+    }
+}
+
+/// Whether the `(` at the start of a condition is closed just before the block
+/// which follows the condition, rather than somewhere inside the condition.
+fn parenthesizes_condition(p: &mut Parser<'_>) -> bool {
+    let mut depth = 0_usize;
+    let mut tokens = p.lookahead();
+    while let Some(kind) = tokens.next() {
+        match kind {
+            t!['('] => depth += 1,
+            t![')'] => {
+                depth -= 1;
+                if depth == 0 {
+                    return tokens.next() == Some(t!['{']);
+                }
+            }
+            // the `)` is missing, which `expect_closing` reports
+            t![;] => break,
+            _ => {}
+        }
+    }
+    true
+}
+
+fn branch_block(p: &mut Parser<'_>) -> bool {
+    match p.peek() {
+        t!['{'] => {
+            let m = p.open();
+            expr_block(p, m);
+            true
+        }
+        found => {
+            expected!(p, found, '{');
+            false
+        }
+    }
+}
+
+test!(if_else, "fn foo() = if a < b { a } else { b };");
+test!(if_else_if, "fn foo() = if a { 1 } else if b { 2 } else { 3 };");
+test!(if_missing_else, "fn foo() = if a { 1 };");
+test!(if_missing_else_chain, "fn foo() = if a { 1 } else if b { 2 };");
+test!(if_statement, "fn foo() = { if a { f(a) }; b };");
+test!(if_statement_tail, "fn foo() = { let x = 1; if a { x } };");
+test!(if_parenthesized, "fn foo() = if (a == b) { 1 } else { 2 };");
+test!(if_parenthesized_operand, "fn foo() = if (a) == b { 1 } else { 2 };");
+test!(if_parenthesized_nested, "fn foo() = if (f(a) + (b)) { 1 } else { 2 };");
+test!(if_else_without_block, "fn foo() = if a { 1 } else 2;");
+test!(if_missing_block, "fn foo() = if a 1;");
+
+test!(block_unclosed, "fn foo() = { let x = 1;\nfn bar() = 2;");
+
+fn match_arm_list(p: &mut Parser<'_>) {
+    let m = p.open();
+    p.expect(t!['{']);
+    while !matches!(p.peek(), t!['}'] | t![eof]) && !at_item_start(p) {
+        // a token which ends an enclosing construct is left to
+        // `expect_closing`, rather than reported as a missing pattern
+        if !at_pat_start(p) && RECOVERY.contains(&p.peek()) {
+            break;
+        }
+        match_arm(p);
+    }
+    expect_closing(p, t!['}']);
+    p.close(m, Kind::MatchArmList);
+}
+
+fn match_arm(p: &mut Parser<'_>) {
+    let m = p.open();
+    pat(p);
+    p.expect(t![=>]);
+    expr(p);
+    if p.peek() != t!['}'] && (p.peek() == t![,] || at_pat_start(p)) {
+        p.expect(t![,]);
+    }
+    p.close(m, Kind::MatchArm);
+}
+
+test!(match_std_map, "fn foo() = match o { Some(x) => Some(f(x)), None => None };");
+test!(match_trailing_comma, "fn foo() = match o { _ => 1, };");
+test!(match_empty, "fn foo() = match o {};");
+test!(match_scrutinee_expr, "fn foo() = match f(a) + 1 { _ => 1 };");
+test!(match_missing_comma, "fn foo() = match o { a => 1 b => 2 };");
+test!(match_missing_arrow, "fn foo() = match o { a 1, b => 2 };");
+
+/// The tokens which can begin a pattern.
+const PAT_FIRST: &[Kind] =
+    &[t![_], t![ident], t![-], t![int], t![float], t![bool], t![string], t![raw_string], t![char]];
+
+#[track_caller]
+fn at_pat_start(p: &mut Parser<'_>) -> bool {
+    PAT_FIRST.contains(&p.peek())
+}
+
+/// The tokens at which parsing resumes after a malformed pattern: those which
+/// can follow a pattern, or end the enclosing construct.
+const PAT_RECOVERY: [Kind; 15] =
+    with_item_first([t![=>], t![,], t![|], t![=], t![:], t![;], t![')'], t![']'], t!['}']]);
+
+fn pat(p: &mut Parser<'_>) {
+    let Some(first) = pat_single(p) else {
+        return;
+    };
+    if p.peek() == t![|] {
+        let m = p.open_before(first);
+        while p.optional(t![|]) {
+            pat_single(p);
+        }
+        p.close(m, Kind::PatOr);
+    }
+}
+
+fn pat_single(p: &mut Parser<'_>) -> Option<MarkClosed> {
+    let m = p.open();
+    match p.peek() {
+        t![_] => {
+            p.bump();
+            Some(p.close(m, Kind::PatWildcard))
+        }
+        // whether a lone name binds a variable or refers to a constructor
+        // without a payload, such as `None`, is decided by name resolution
+        t![ident] => {
+            p.bump();
+            if p.peek() == t!['('] {
+                pat_arg_list(p);
+                Some(p.close(m, Kind::PatConstructor))
+            } else {
+                Some(p.close(m, Kind::PatBinding))
+            }
+        }
+        t![int] | t![float] | t![bool] | t![string] | t![raw_string] | t![char] => {
+            p.bump();
+            Some(p.close(m, Kind::PatLiteral))
+        }
+        t![-] => {
+            p.bump();
+            match p.peek() {
+                t![int] | t![float] => p.bump(),
+                found => {
+                    expected!(p, found, int, float);
+                    p.recover(&PAT_RECOVERY);
+                }
+            }
+            Some(p.close(m, Kind::PatLiteral))
+        }
+        found => {
+            expected!(p, found, _, ident, -, int, float, bool, string, raw_string, char ("a pattern"));
+            p.skip_until(&PAT_RECOVERY);
+            p.close(m, Kind::Error);
+            None
+        }
+    }
+}
+
+fn pat_arg_list(p: &mut Parser<'_>) {
+    let m = p.open();
+    p.expect(t!['(']);
+    while p.peek() != t![')'] && at_pat_start(p) {
+        pat(p);
+        if p.peek() != t![')'] && (p.peek() == t![,] || at_pat_start(p)) {
+            p.expect(t![,]);
+        }
+    }
+    expect_closing(p, t![')']);
+    p.close(m, Kind::PatArgList);
+}
+
+test!(pat_wildcard, "fn foo() = match o { _ => 0 };");
+test!(pat_binding, "fn foo() = match o { x => x };");
+test!(pat_literal, "fn foo() = match o { 1 => a, -2 => b, 'c' => c, \"d\" => d, true => e };");
+test!(pat_literal_bad_negation, "fn foo() = match o { -a => 1 };");
+test!(pat_constructor, "fn foo() = match o { Some(x) => x, None => 0 };");
+test!(pat_constructor_multiple, "fn foo() = match o { Pair(a, _,) => a };");
+test!(pat_nested, "fn foo() = match o { Some(Pair(Some(x), _)) => x };");
+test!(pat_or, "fn foo() = match o { Less | Equal => 0, Greater => 1 };");
+test!(pat_or_nested, "fn foo() = match o { Some(1 | 2) => 0 };");
+test!(pat_invalid, "fn foo() = match o { + => 0 };");
+
 #[cfg(test)]
 fn test_output(input: &str) -> String {
     let db = &crate::Database::default();
-    let file = crate::source::File::new(db, "<test>".into(), input.to_owned());
-    let (tree, diagnostics) = crate::syntax::parse(db, file);
+    let (tree, diagnostics) = crate::syntax::parse(db, crate::syntax::test_file(db, input));
+    tree.check_invariants(input);
     let mut writer = crate::terminal::diagnostic::StringWriter(String::new());
-    tree.debug(&mut writer.0, input).unwrap();
+    tree.debug(&mut writer.0).unwrap();
     writeln!(&mut writer.0).unwrap();
     crate::terminal::diagnostic::emit(db, &diagnostics, &mut termcolor::NoColor::new(&mut writer))
         .unwrap();
@@ -136,3 +1323,65 @@ macro_rules! test {
     };
 }
 use test;
+
+mod errors {
+    pub(super) fn missing_else(
+        file: crate::source::File,
+        span: crate::source::Span,
+    ) -> crate::diagnostic::Diagnostic {
+        let mut diagnostic =
+            crate::diagnostic::Diagnostic::error("`if` used as a value has no `else` branch");
+        diagnostic.primary(file, span, "this `if` has no `else`");
+        diagnostic.note("add an `else` branch giving the value for when the condition is false");
+        diagnostic
+    }
+
+    pub(super) fn parenthesized_condition(
+        file: crate::source::File,
+        span: crate::source::Span,
+    ) -> crate::diagnostic::Diagnostic {
+        let mut diagnostic =
+            crate::diagnostic::Diagnostic::error("conditions are not wrapped in parentheses");
+        diagnostic.primary(file, span, "remove this parenthesis and its matching `)`");
+        diagnostic.note("to group an expression, use `{ ... }`");
+        diagnostic
+    }
+
+    pub(super) fn parenthesized_expr(
+        file: crate::source::File,
+        span: crate::source::Span,
+    ) -> crate::diagnostic::Diagnostic {
+        let mut diagnostic =
+            crate::diagnostic::Diagnostic::error("parentheses don't group expressions");
+        diagnostic.primary(file, span, "remove this parenthesis and its matching `)`");
+        diagnostic.note("to group an expression, use `{ ... }`");
+        diagnostic
+    }
+
+    pub(super) fn missing_semicolon(
+        file: crate::source::File,
+        previous: crate::source::Span,
+        found: crate::syntax::kind::Kind,
+    ) -> crate::diagnostic::Diagnostic {
+        let mut diagnostic = crate::diagnostic::Diagnostic::error(format!(
+            "expected `;` after statement, found {found}"
+        ));
+        let end = crate::source::Span::new(previous.end, previous.end);
+        diagnostic.primary(file, end, "add `;` here");
+        diagnostic.note("statements in a block are separated by `;`");
+        diagnostic
+    }
+
+    pub(super) fn chained_comparison(
+        file: crate::source::File,
+        span: crate::source::Span,
+    ) -> crate::diagnostic::Diagnostic {
+        let mut diagnostic =
+            crate::diagnostic::Diagnostic::error("comparison operators cannot be chained");
+        diagnostic.primary(file, span, "second comparison here");
+        diagnostic.note(
+            "to compare more than two values, join comparisons with `&`, as in `a < b & b < c`",
+        );
+        diagnostic
+    }
+}
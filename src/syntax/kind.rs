@@ -1,61 +1,152 @@
 kinds! {
     tokens {
-        // token     ([macro     ] display             )
-        Whitespace   ([whitespace] "whitespace"        )
-        Comment      ([comment   ] "a comment"         )
-
-        OpenParen    (['('       ] "`(`"               )
-        CloseParen   ([')'       ] "`)`"               )
-        OpenBracket  (['['       ] "`[`"               )
-        CloseBracket ([']'       ] "`]`"               )
-        OpenBrace    (['{'       ] "`{`"               )
-        CloseBrace   (['}'       ] "`}`"               )
-
-        Dot          ([.         ] "`.`"               )
-        Comma        ([,         ] "`,`"               )
-        Colon        ([:         ] "`:`"               )
-        Semi         ([;         ] "`;`"               )
-        Bang         ([!         ] "`!`"               )
-        Equal        ([=         ] "`=`"               )
-        Arrow        ([->        ] "`->`"              )
-
-        Plus         ([+         ] "`+`"               )
-        Minus        ([-         ] "`-`"               )
-        Star         ([*         ] "`*`"               )
-        Slash        ([/         ] "`/`"               )
-
-        BoolLiteral  ([bool      ] "a boolean literal" )
-        IntLiteral   ([int       ] "an integer literal")
-
-        Ident        ([ident     ] "an identifier"     )
-        FnKw         ([fn        ] "`fn`"              )
-
-        Unknown      ([unknown   ] "an unknown token"  )
-        Eof          ([eof       ] "the end of input"  )
+        // token         ([macro            ] display               )
+        Whitespace       ([whitespace       ] "whitespace"          )
+        Comment          ([comment          ] "a comment"           )
+        BlockComment     ([block_comment    ] "a block comment"     )
+        DocComment       ([doc_comment      ] "a doc comment"       )
+        InnerDocComment  ([inner_doc_comment] "an inner doc comment")
+
+        OpenParen        (['('              ] "`(`"                 )
+        CloseParen       ([')'              ] "`)`"                 )
+        OpenBracket      (['['              ] "`[`"                 )
+        CloseBracket     ([']'              ] "`]`"                 )
+        OpenBrace        (['{'              ] "`{`"                 )
+        CloseBrace       (['}'              ] "`}`"                 )
+
+        Dot              ([.                ] "`.`"                 )
+        Comma            ([,                ] "`,`"                 )
+        Colon            ([:                ] "`:`"                 )
+        Semi             ([;                ] "`;`"                 )
+        Bang             ([!                ] "`!`"                 )
+        Equal            ([=                ] "`=`"                 )
+        EqualEqual       ([==               ] "`==`"                )
+        BangEqual        ([!=               ] "`!=`"                )
+        Less             ([<                ] "`<`"                 )
+        LessEqual        ([<=               ] "`<=`"                )
+        Greater          ([>                ] "`>`"                 )
+        GreaterEqual     ([>=               ] "`>=`"                )
+        Arrow            ([->               ] "`->`"                )
+        FatArrow         ([=>               ] "`=>`"                )
+        Pipe             ([|                ] "`|`"                 )
+        Ampersand        ([&                ] "`&`"                 )
+        Tilde            ([~                ] "`~`"                 )
+        Underscore       ([_                ] "`_`"                 )
+
+        Plus             ([+                ] "`+`"                 )
+        Minus            ([-                ] "`-`"                 )
+        Star             ([*                ] "`*`"                 )
+        Slash            ([/                ] "`/`"                 )
+
+        BoolLiteral      ([bool             ] "a boolean literal"   )
+        IntLiteral       ([int              ] "an integer literal"  )
+        FloatLiteral     ([float            ] "a float literal"     )
+        StringLiteral    ([string           ] "a string literal"    )
+        RawStringLiteral ([raw_string       ] "a raw string literal")
+        CharLiteral      ([char             ] "a character literal" )
+
+        Ident            ([ident            ] "an identifier"       )
+        ElseKw           ([else             ] "`else`"              )
+        FnKw             ([fn               ] "`fn`"                )
+        IfKw             ([if               ] "`if`"                )
+        LetKw            ([let              ] "`let`"               )
+        MatchKw          ([match            ] "`match`"             )
+        ModKw            ([mod              ] "`mod`"               )
+        PubKw            ([pub              ] "`pub`"               )
+        TraitKw          ([trait            ] "`trait`"             )
+        TypeKw           ([type             ] "`type`"              )
+        UseKw            ([use              ] "`use`"               )
+        WithKw           ([with             ] "`with`"              )
+
+        Unknown          ([unknown          ] "an unknown token"    )
+        Eof              ([eof              ] "the end of input"    )
     }
 
     nodes {
         Root
         Error
 
+        Visibility
+        Mod
+        ItemList
+        Use
+        UseTree
+        UseTreeList
+        Path
         Fn
         ParamList
         Param
-        TypeExpr
+        Type
+        GenericParamList
+        GenericParam
+        TypeSum
+        Variant
+        VariantPayload
+        Trait
+        TraitMemberList
+        WithClause
+        BoundList
+        Bound
+        TypePath
+        TypeApplied
+        TypeArgList
+        TypeFn
+        TypeList
+        TypeTuple
+        TypeUnit
+        TypeParen
+        TypeRecord
+        TypeRecordField
         ExprLiteral
         ExprName
-        ExprGroup
+        ExprBlock
+        StmtLet
+        StmtExpr
+        ExprUnary
+        ExprBinary
+        ExprCall
+        ArgList
+        Arg
+        ExprField
+        ExprIndex
+        ExprList
+        ExprRecord
+        RecordFieldList
+        RecordField
+        ExprIf
+        ExprLambda
+        LambdaParamList
+        LambdaParam
+        ExprMatch
+        MatchArmList
+        MatchArm
+
+        PatWildcard
+        PatBinding
+        PatLiteral
+        PatConstructor
+        PatArgList
+        PatOr
     }
 }
 
 impl Kind {
-    #[cfg(test)]
     pub(super) fn is_token(self) -> bool {
         (self as u8) < (Self::_LastToken as u8)
     }
 
+    /// Whether the parser should skip over this kind. Unknown tokens count as
+    /// trivia, because the lexer has already reported them.
     pub(super) fn is_trivia(self) -> bool {
-        matches!(self, Self::Whitespace | Self::Comment)
+        matches!(
+            self,
+            Self::Whitespace
+                | Self::Comment
+                | Self::BlockComment
+                | Self::DocComment
+                | Self::InnerDocComment
+                | Self::Unknown
+        )
     }
 }
 
@@ -79,6 +170,12 @@ macro_rules! kinds {
             $($node,)*
         }
 
+        impl Kind {
+            /// Every kind of node, as opposed to token.
+            #[cfg(test)]
+            pub(super) const NODES: &[Kind] = &[$(Self::$node),*];
+        }
+
         impl ::core::fmt::Display for Kind {
             fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> ::core::fmt::Result {
                 match self {
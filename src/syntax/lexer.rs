@@ -16,21 +16,32 @@ impl Token {
 }
 
 pub(super) struct Lexer<'text> {
+    file: crate::source::File,
     text: &'text str,
     chars: core::str::Chars<'text>,
     start: u32,
     current: u32,
+    diagnostics: Vec<crate::diagnostic::Diagnostic>,
 }
 
 impl<'text> Lexer<'text> {
-    pub(super) fn new(text: &'text str) -> Self {
-        Self { text, chars: text.chars(), start: 0, current: 0 }
+    pub(super) fn new(file: crate::source::File, text: &'text str) -> Self {
+        Self { file, text, chars: text.chars(), start: 0, current: 0, diagnostics: Vec::new() }
+    }
+
+    /// Take all diagnostics produced since the last call.
+    pub(super) fn take_diagnostics(&mut self) -> Vec<crate::diagnostic::Diagnostic> {
+        core::mem::take(&mut self.diagnostics)
     }
 
     fn peek(&self) -> char {
         self.chars.clone().next().unwrap_or(EOF_CHAR)
     }
 
+    fn peek_second(&self) -> char {
+        self.chars.clone().nth(1).unwrap_or(EOF_CHAR)
+    }
+
     fn is_eof(&self) -> bool {
         self.chars.as_str().is_empty()
     }
@@ -63,29 +74,328 @@ impl<'text> Lexer<'text> {
         t![whitespace]
     }
 
-    fn comment(&mut self) -> Kind {
+    fn line_comment(&mut self) -> Kind {
+        self.bump();
+        // `////` and longer are ordinary comments, matching what most editors
+        // assume.
+        let kind = match (self.peek(), self.peek_second()) {
+            ('/', '/') => t![comment],
+            ('/', _) => t![doc_comment],
+            ('!', _) => t![inner_doc_comment],
+            _ => t![comment],
+        };
         self.eat_while(|c| c != '\n');
-        t![comment]
+        kind
+    }
+
+    fn block_comment(&mut self) -> Kind {
+        self.bump();
+        let mut depth = 1_u32;
+        while depth > 0 {
+            match self.bump() {
+                Some('/') if self.peek() == '*' => {
+                    self.bump();
+                    depth += 1;
+                }
+                Some('*') if self.peek() == '/' => {
+                    self.bump();
+                    depth -= 1;
+                }
+                Some(_) => {}
+                None => {
+                    let opening = Span::new(self.start, self.start + 2);
+                    self.diagnostics.push(errors::unterminated_block_comment(self.file, opening));
+                    break;
+                }
+            }
+        }
+        t![block_comment]
+    }
+
+    fn string(&mut self) -> Kind {
+        loop {
+            match self.peek() {
+                '"' => {
+                    self.bump();
+                    break;
+                }
+                '\\' => self.escape(),
+                _ if self.is_eof() => {
+                    let opening = Span::new(self.start, self.start + 1);
+                    self.diagnostics.push(errors::unterminated_string(self.file, opening));
+                    break;
+                }
+                _ => {
+                    self.bump();
+                }
+            }
+        }
+        t![string]
+    }
+
+    fn raw_string(&mut self) -> Kind {
+        let mut hashes = 0_usize;
+        while self.peek() == '#' {
+            self.bump();
+            hashes += 1;
+        }
+        if self.peek() != '"' {
+            let prefix = Span::new(self.start, self.current);
+            self.diagnostics.push(errors::raw_string_missing_quote(self.file, prefix));
+            return t![raw_string];
+        }
+        self.bump();
+        let opening = Span::new(self.start, self.current);
+        loop {
+            match self.bump() {
+                Some('"') => {
+                    let rest = self.chars.as_str();
+                    let closing_hashes = rest.bytes().take(hashes).take_while(|&b| b == b'#');
+                    if closing_hashes.count() == hashes {
+                        for _ in 0..hashes {
+                            self.bump();
+                        }
+                        break;
+                    }
+                }
+                Some(_) => {}
+                None => {
+                    self.diagnostics.push(errors::unterminated_raw_string(self.file, opening));
+                    break;
+                }
+            }
+        }
+        t![raw_string]
+    }
+
+    fn char(&mut self) -> Kind {
+        let opening = Span::new(self.start, self.start + 1);
+        match self.peek() {
+            '\'' => {
+                self.bump();
+                let span = Span::new(self.start, self.current);
+                self.diagnostics.push(errors::empty_char(self.file, span));
+                return t![char];
+            }
+            '\\' => self.escape(),
+            '\n' => {
+                self.diagnostics.push(errors::unterminated_char(self.file, opening));
+                return t![char];
+            }
+            _ if self.is_eof() => {
+                self.diagnostics.push(errors::unterminated_char(self.file, opening));
+                return t![char];
+            }
+            _ => {
+                self.bump();
+            }
+        }
+        if self.peek() == '\'' {
+            self.bump();
+            return t![char];
+        }
+        // a literal holds a single character, so one which isn't closed just
+        // after it is unterminated, rather than running on over the `;` in
+        // `'a;`, unless a closing `'` ends the word, as in `'ab'`
+        let rest = self.chars.as_str();
+        let word = rest.find(|c: char| !c.is_alphanumeric() && c != '_' && c != '\\');
+        if word.is_some_and(|length| length > 0 && rest[length..].starts_with('\'')) {
+            self.eat_while(|c| c != '\'');
+            self.bump();
+            let span = Span::new(self.start, self.current);
+            self.diagnostics.push(errors::char_too_long(self.file, span));
+        } else {
+            self.diagnostics.push(errors::unterminated_char(self.file, opening));
+        }
+        t![char]
+    }
+
+    /// Lex a single escape sequence within a string or character literal,
+    /// starting at the backslash.
+    fn escape(&mut self) {
+        let start = self.current;
+        self.bump();
+        match self.peek() {
+            'n' | 'r' | 't' | '0' | '\\' | '"' | '\'' => {
+                self.bump();
+            }
+            'u' => {
+                self.bump();
+                self.unicode_escape(start);
+            }
+            // an unterminated literal is reported by the caller
+            _ if self.is_eof() => {}
+            _ => {
+                self.bump();
+                let span = Span::new(start, self.current);
+                self.diagnostics.push(errors::unknown_escape(self.file, span));
+            }
+        }
+    }
+
+    fn unicode_escape(&mut self, start: u32) {
+        if self.peek() != '{' {
+            let span = Span::new(start, self.current);
+            self.diagnostics.push(errors::unicode_escape_missing_brace(self.file, span));
+            return;
+        }
+        self.bump();
+        let digits_start = self.current;
+        self.eat_while(|c| c.is_ascii_hexdigit());
+        let digits_span = Span::new(digits_start, self.current);
+        if self.peek() != '}' {
+            let span = Span::new(start, self.current);
+            self.diagnostics.push(errors::unterminated_unicode_escape(self.file, span));
+            return;
+        }
+        self.bump();
+        let span = Span::new(start, self.current);
+        let digits = &self.text[digits_span];
+        if digits.is_empty() || digits.len() > 6 {
+            self.diagnostics.push(errors::unicode_escape_length(self.file, span));
+        } else if char::from_u32(u32::from_str_radix(digits, 16).unwrap()).is_none() {
+            self.diagnostics.push(errors::invalid_unicode_escape(self.file, span));
+        }
     }
 
-    fn int(&mut self) -> Kind {
-        self.eat_while(|c| c.is_ascii_digit());
-        t![int]
+    fn number(&mut self, first: char) -> Kind {
+        if first == '0' && matches!(self.peek(), 'x' | 'o' | 'b') {
+            let radix = match self.bump() {
+                Some('x') => Radix::Hexadecimal,
+                Some('o') => Radix::Octal,
+                _ => Radix::Binary,
+            };
+            let digits_start = self.current;
+            self.eat_while(|c| c.is_ascii_alphanumeric() || c == '_');
+            if self.text[Span::new(digits_start, self.current)].chars().any(|c| c != '_') {
+                self.check_digits(digits_start, radix);
+            } else {
+                let span = Span::new(self.start, self.current);
+                self.diagnostics.push(errors::missing_digits(self.file, span, radix));
+            }
+            return t![int];
+        }
+
+        let diagnostics = self.diagnostics.len();
+        let mut kind = t![int];
+        self.eat_while(|c| c.is_ascii_digit() || c == '_');
+        if self.peek() == '.' && self.peek_second().is_ascii_digit() {
+            self.bump();
+            self.eat_while(|c| c.is_ascii_digit() || c == '_');
+            kind = t![float];
+        }
+        if matches!(self.peek(), 'e' | 'E') {
+            let exponent_start = self.current;
+            self.bump();
+            if matches!(self.peek(), '+' | '-') {
+                self.bump();
+            }
+            let digits_start = self.current;
+            self.eat_while(|c| c.is_ascii_digit() || c == '_');
+            if !self.text[Span::new(digits_start, self.current)].chars().any(|c| c != '_') {
+                // the rest of the token, as in `1else`, is part of the same
+                // mistake, so it isn't reported again as invalid digits
+                let span = Span::new(exponent_start, self.current);
+                self.diagnostics.push(errors::missing_exponent_digits(self.file, span));
+                self.eat_while(|c| c.is_ascii_alphanumeric() || c == '_');
+                return t![float];
+            }
+            kind = t![float];
+        }
+        let suffix_start = self.current;
+        self.eat_while(|c| c.is_ascii_alphanumeric() || c == '_');
+        self.check_digits(suffix_start, Radix::Decimal);
+        // a malformed literal has already been reported
+        let span = Span::new(self.start, self.current);
+        if kind == t![float]
+            && self.diagnostics.len() == diagnostics
+            && super::literal::float(&self.text[span]).is_none()
+        {
+            self.diagnostics.push(errors::float_out_of_range(self.file, span));
+        }
+        kind
     }
 
-    fn word(&mut self, first: char) -> Kind {
-        let start = usize::try_from(self.start).unwrap() + 1 - first.len_utf8();
+    /// Report the first character since `start` which is not a valid digit
+    /// in the given radix.
+    fn check_digits(&mut self, start: u32, radix: Radix) {
+        let digits = &self.text[Span::new(start, self.current)];
+        let invalid = digits.char_indices().find(|&(_, c)| c != '_' && !c.is_digit(radix as u32));
+        if let Some((index, c)) = invalid {
+            let index = start + u32::try_from(index).unwrap();
+            let span = Span::new(index, index + u32::try_from(c.len_utf8()).unwrap());
+            self.diagnostics.push(errors::invalid_digit(self.file, span, c, radix));
+        }
+    }
+
+    fn unknown(&mut self) -> Kind {
+        self.eat_while(is_unknown);
+        let span = Span::new(self.start, self.current);
+        self.diagnostics.push(errors::unknown_characters(self.file, span, &self.text[span]));
+        t![unknown]
+    }
+
+    fn word(&mut self) -> Kind {
         self.eat_while(unicode_xid::UnicodeXID::is_xid_continue);
-        let end = usize::try_from(self.current).unwrap();
-        let word = &self.text[start..end];
-        match word {
+        let span = Span::new(self.start, self.current);
+        match &self.text[span] {
+            "_" => t![_],
             "true" | "false" => t![bool],
+            "else" => t![else],
             "fn" => t![fn],
+            "if" => t![if],
+            "let" => t![let],
+            "match" => t![match],
+            "mod" => t![mod],
+            "pub" => t![pub],
+            "trait" => t![trait],
+            "type" => t![type],
+            "use" => t![use],
+            "with" => t![with],
+            word if RESERVED_KEYWORDS.contains(&word) => {
+                self.diagnostics.push(errors::reserved_keyword(self.file, span, word));
+                t![ident]
+            }
             _ => t![ident],
         }
     }
 }
 
+/// Words which are not used by the language yet, but are kept free so that
+/// they can be made into keywords later.
+const RESERVED_KEYWORDS: &[&str] =
+    &["as", "break", "continue", "for", "in", "loop", "return", "while"];
+
+/// Whether a character cannot begin any token. Runs of these are lexed as a
+/// single [`Kind::Unknown`] token so that they are only reported once.
+fn is_unknown(c: char) -> bool {
+    !(c.is_whitespace()
+        || c.is_ascii_alphanumeric()
+        || c == '_'
+        || unicode_xid::UnicodeXID::is_xid_start(c)
+        || "()[]{}.,:;!=<>|&~+-*/\"'".contains(c))
+}
+
+/// The base of a numeric literal.
+#[derive(Clone, Copy)]
+pub(super) enum Radix {
+    Binary = 2,
+    Octal = 8,
+    Decimal = 10,
+    Hexadecimal = 16,
+}
+
+impl core::fmt::Display for Radix {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::Binary => f.write_str("binary"),
+            Self::Octal => f.write_str("octal"),
+            Self::Decimal => f.write_str("decimal"),
+            Self::Hexadecimal => f.write_str("hexadecimal"),
+        }
+    }
+}
+
 impl Iterator for Lexer<'_> {
     type Item = Token;
 
@@ -93,7 +403,8 @@ impl Iterator for Lexer<'_> {
         let first = self.bump()?;
         let kind = match first {
             c if c.is_whitespace() => self.whitespace(),
-            '#' => self.comment(),
+            '/' if self.peek() == '/' => self.line_comment(),
+            '/' if self.peek() == '*' => self.block_comment(),
 
             '(' => t!['('],
             ')' => t![')'],
@@ -106,21 +417,372 @@ impl Iterator for Lexer<'_> {
             ',' => t![,],
             ':' => t![:],
             ';' => t![;],
+            '!' if self.peek() == '=' => self.bump_with(t![!=]),
             '!' => t![!],
+            '=' if self.peek() == '=' => self.bump_with(t![==]),
+            '=' if self.peek() == '>' => self.bump_with(t![=>]),
             '=' => t![=],
+            '<' if self.peek() == '=' => self.bump_with(t![<=]),
+            '<' => t![<],
+            '>' if self.peek() == '=' => self.bump_with(t![>=]),
+            '>' => t![>],
             '-' if self.peek() == '>' => self.bump_with(t![->]),
+            '|' => t![|],
+            '&' => t![&],
+            '~' => t![~],
+
+            '"' => self.string(),
+            '\'' => self.char(),
+            'r' if matches!(self.peek(), '"' | '#') => self.raw_string(),
 
-            '-' if self.peek().is_ascii_digit() => self.int(),
-            c if c.is_ascii_digit() => self.int(),
-            c if unicode_xid::UnicodeXID::is_xid_start(c) || c == '_' => self.word(c),
+            c if c.is_ascii_digit() => self.number(c),
+            c if unicode_xid::UnicodeXID::is_xid_start(c) || c == '_' => self.word(),
 
             '+' => t![+],
             '-' => t![-],
             '*' => t![*],
             '/' => t![/],
 
-            _ => t![unknown],
+            _ => self.unknown(),
         };
         Some(Token::new(kind, self.finish_span()))
     }
 }
+
+mod errors {
+    pub(super) fn unterminated_block_comment(
+        file: crate::source::File,
+        opening: crate::source::Span,
+    ) -> crate::diagnostic::Diagnostic {
+        let mut diagnostic = crate::diagnostic::Diagnostic::error("unterminated block comment");
+        diagnostic.primary(file, opening, "block comment opened here");
+        diagnostic.note("block comments nest, so each `/*` needs its own `*/`");
+        diagnostic
+    }
+
+    pub(super) fn unterminated_string(
+        file: crate::source::File,
+        opening: crate::source::Span,
+    ) -> crate::diagnostic::Diagnostic {
+        let mut diagnostic = crate::diagnostic::Diagnostic::error("unterminated string literal");
+        diagnostic.primary(file, opening, "string literal opened here");
+        diagnostic
+    }
+
+    pub(super) fn raw_string_missing_quote(
+        file: crate::source::File,
+        prefix: crate::source::Span,
+    ) -> crate::diagnostic::Diagnostic {
+        let mut diagnostic = crate::diagnostic::Diagnostic::error("expected `\"` in raw string");
+        diagnostic.primary(file, prefix, "raw string prefix here");
+        diagnostic.note("raw strings are written `r\"...\"`, `r#\"...\"#`, and so on");
+        diagnostic
+    }
+
+    pub(super) fn unterminated_raw_string(
+        file: crate::source::File,
+        opening: crate::source::Span,
+    ) -> crate::diagnostic::Diagnostic {
+        let mut diagnostic =
+            crate::diagnostic::Diagnostic::error("unterminated raw string literal");
+        diagnostic.primary(file, opening, "raw string literal opened here");
+        diagnostic.note("a raw string must be closed by `\"` and as many `#`s as it opened with");
+        diagnostic
+    }
+
+    pub(super) fn unterminated_char(
+        file: crate::source::File,
+        opening: crate::source::Span,
+    ) -> crate::diagnostic::Diagnostic {
+        let mut diagnostic = crate::diagnostic::Diagnostic::error("unterminated character literal");
+        diagnostic.primary(file, opening, "character literal opened here");
+        diagnostic
+    }
+
+    pub(super) fn empty_char(
+        file: crate::source::File,
+        span: crate::source::Span,
+    ) -> crate::diagnostic::Diagnostic {
+        let mut diagnostic = crate::diagnostic::Diagnostic::error("empty character literal");
+        diagnostic.primary(file, span, "expected a character between the quotes");
+        diagnostic
+    }
+
+    pub(super) fn char_too_long(
+        file: crate::source::File,
+        span: crate::source::Span,
+    ) -> crate::diagnostic::Diagnostic {
+        let mut diagnostic = crate::diagnostic::Diagnostic::error(
+            "character literal may only contain one character",
+        );
+        diagnostic.primary(file, span, "found multiple characters here");
+        diagnostic.note("if you meant to write a string, use double quotes (`\"`)");
+        diagnostic
+    }
+
+    pub(super) fn unknown_escape(
+        file: crate::source::File,
+        span: crate::source::Span,
+    ) -> crate::diagnostic::Diagnostic {
+        let mut diagnostic = crate::diagnostic::Diagnostic::error("unknown escape sequence");
+        diagnostic.primary(file, span, "unknown escape sequence");
+        diagnostic.note(
+            "valid escapes are `\\n`, `\\r`, `\\t`, `\\0`, `\\\\`, `\\\"`, `\\'`, and `\\u{...}`",
+        );
+        diagnostic
+    }
+
+    pub(super) fn unicode_escape_missing_brace(
+        file: crate::source::File,
+        span: crate::source::Span,
+    ) -> crate::diagnostic::Diagnostic {
+        let mut diagnostic = crate::diagnostic::Diagnostic::error("expected `{` in unicode escape");
+        diagnostic.primary(file, span, "unicode escape here");
+        diagnostic.note("unicode escapes are written `\\u{...}`, with 1 to 6 hex digits");
+        diagnostic
+    }
+
+    pub(super) fn unterminated_unicode_escape(
+        file: crate::source::File,
+        span: crate::source::Span,
+    ) -> crate::diagnostic::Diagnostic {
+        let mut diagnostic = crate::diagnostic::Diagnostic::error("unterminated unicode escape");
+        diagnostic.primary(file, span, "expected `}` after this");
+        diagnostic.note("unicode escapes are written `\\u{...}`, with 1 to 6 hex digits");
+        diagnostic
+    }
+
+    pub(super) fn unicode_escape_length(
+        file: crate::source::File,
+        span: crate::source::Span,
+    ) -> crate::diagnostic::Diagnostic {
+        let mut diagnostic = crate::diagnostic::Diagnostic::error(
+            "unicode escape must have between 1 and 6 hex digits",
+        );
+        diagnostic.primary(file, span, "invalid unicode escape");
+        diagnostic
+    }
+
+    pub(super) fn invalid_unicode_escape(
+        file: crate::source::File,
+        span: crate::source::Span,
+    ) -> crate::diagnostic::Diagnostic {
+        let mut diagnostic =
+            crate::diagnostic::Diagnostic::error("unicode escape is not a valid character");
+        diagnostic.primary(file, span, "invalid unicode escape");
+        diagnostic.note("surrogates and values above `10FFFF` are not characters");
+        diagnostic
+    }
+
+    pub(super) fn missing_digits(
+        file: crate::source::File,
+        span: crate::source::Span,
+        radix: super::Radix,
+    ) -> crate::diagnostic::Diagnostic {
+        let mut diagnostic =
+            crate::diagnostic::Diagnostic::error(format!("missing digits in {radix} literal"));
+        diagnostic.primary(file, span, "expected at least one digit after the prefix");
+        diagnostic
+    }
+
+    pub(super) fn missing_exponent_digits(
+        file: crate::source::File,
+        span: crate::source::Span,
+    ) -> crate::diagnostic::Diagnostic {
+        let mut diagnostic = crate::diagnostic::Diagnostic::error("missing digits in exponent");
+        diagnostic.primary(file, span, "expected at least one digit after this");
+        diagnostic
+    }
+
+    pub(super) fn float_out_of_range(
+        file: crate::source::File,
+        span: crate::source::Span,
+    ) -> crate::diagnostic::Diagnostic {
+        let mut diagnostic = crate::diagnostic::Diagnostic::error("float literal is out of range");
+        diagnostic.primary(file, span, "too large to be represented");
+        diagnostic
+    }
+
+    pub(super) fn invalid_digit(
+        file: crate::source::File,
+        span: crate::source::Span,
+        digit: char,
+        radix: super::Radix,
+    ) -> crate::diagnostic::Diagnostic {
+        let mut diagnostic = crate::diagnostic::Diagnostic::error(format!(
+            "invalid digit `{digit}` in {radix} literal"
+        ));
+        diagnostic.primary(file, span, "invalid digit");
+        diagnostic
+    }
+
+    pub(super) fn unknown_characters(
+        file: crate::source::File,
+        span: crate::source::Span,
+        text: &str,
+    ) -> crate::diagnostic::Diagnostic {
+        let mut chars = text.chars();
+        let mut diagnostic = match (chars.next(), chars.next()) {
+            (Some(c), None) => {
+                let mut diagnostic = crate::diagnostic::Diagnostic::error(format!(
+                    "unknown character `{c}` ({})",
+                    code_point(c)
+                ));
+                diagnostic.primary(file, span, "unknown character");
+                diagnostic
+            }
+            _ => {
+                let code_points = text.chars().map(code_point).collect::<Vec<_>>().join(", ");
+                let mut diagnostic = crate::diagnostic::Diagnostic::error(format!(
+                    "unknown characters `{text}` ({code_points})"
+                ));
+                diagnostic.primary(file, span, "unknown characters");
+                diagnostic
+            }
+        };
+        let mut seen = Vec::new();
+        for c in text.chars() {
+            if seen.contains(&c) {
+                continue;
+            }
+            seen.push(c);
+            if let Some(help) = confusable_help(c) {
+                diagnostic.note(help);
+            }
+        }
+        diagnostic
+    }
+
+    fn code_point(c: char) -> String {
+        format!("U+{:04X}", u32::from(c))
+    }
+
+    /// Targeted help for characters that are easily mistaken for ones Fury
+    /// understands, such as those produced by "smart" punctuation or an input
+    /// method left in fullwidth mode.
+    fn confusable_help(c: char) -> Option<String> {
+        const LOOKALIKES: &[(char, char)] = &[
+            ('\u{2018}', '\''),
+            ('\u{2019}', '\''),
+            ('\u{201C}', '"'),
+            ('\u{201D}', '"'),
+            ('\u{2010}', '-'),
+            ('\u{2013}', '-'),
+            ('\u{2014}', '-'),
+            ('\u{2212}', '-'),
+            ('\u{00D7}', '*'),
+            ('\u{2217}', '*'),
+            ('\u{00F7}', '/'),
+            ('\u{2044}', '/'),
+            ('\u{037E}', ';'),
+            ('\u{3001}', ','),
+            ('\u{3002}', '.'),
+        ];
+        let code_point = code_point(c);
+        if let Some(&(_, ascii)) = LOOKALIKES.iter().find(|&&(lookalike, _)| lookalike == c) {
+            return Some(format!(
+                "`{c}` ({code_point}) looks like `{ascii}`, but it is a different character"
+            ));
+        }
+        // the fullwidth forms of printable ASCII are offset by a constant
+        if let '\u{FF01}'..='\u{FF5E}' = c {
+            let ascii = char::from_u32(u32::from(c) - 0xFEE0).unwrap();
+            return Some(format!("`{c}` ({code_point}) is the fullwidth form of `{ascii}`"));
+        }
+        if let '\u{200B}'..='\u{200D}' | '\u{2060}' | '\u{FEFF}' = c {
+            return Some(format!("{code_point} is an invisible character, and can be removed"));
+        }
+        None
+    }
+
+    pub(super) fn reserved_keyword(
+        file: crate::source::File,
+        span: crate::source::Span,
+        word: &str,
+    ) -> crate::diagnostic::Diagnostic {
+        let mut diagnostic =
+            crate::diagnostic::Diagnostic::error(format!("`{word}` is a reserved keyword"));
+        diagnostic.primary(file, span, "reserved keyword used as an identifier");
+        diagnostic.note("reserved keywords cannot be used as identifiers");
+        diagnostic
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Lexer;
+    use crate::syntax::kind::Kind::{self, *};
+
+    fn kinds(text: &str) -> Vec<Kind> {
+        let db = crate::Database::default();
+        let file = crate::syntax::test_file(&db, text);
+        Lexer::new(file, text).map(|token| token.kind).filter(|&kind| kind != Whitespace).collect()
+    }
+
+    #[test]
+    fn keywords() {
+        assert_eq!(
+            kinds("else fn if let match mod pub trait type use with"),
+            [ElseKw, FnKw, IfKw, LetKw, MatchKw, ModKw, PubKw, TraitKw, TypeKw, UseKw, WithKw]
+        );
+    }
+
+    #[test]
+    fn keyword_prefixes_are_identifiers() {
+        assert_eq!(kinds("types iff use_ matcher Self"), [Ident, Ident, Ident, Ident, Ident]);
+    }
+
+    #[test]
+    fn underscore() {
+        assert_eq!(kinds("_ _a a_ __"), [Underscore, Ident, Ident, Ident]);
+    }
+
+    #[test]
+    fn reserved_keywords_are_identifiers() {
+        assert_eq!(kinds("return while"), [Ident, Ident]);
+    }
+
+    #[test]
+    fn operators() {
+        assert_eq!(
+            kinds("= == ! != < <= > >= -> => | & ~"),
+            [
+                Equal,
+                EqualEqual,
+                Bang,
+                BangEqual,
+                Less,
+                LessEqual,
+                Greater,
+                GreaterEqual,
+                Arrow,
+                FatArrow,
+                Pipe,
+                Ampersand,
+                Tilde
+            ]
+        );
+    }
+
+    #[test]
+    fn operators_unspaced() {
+        assert_eq!(
+            kinds("a<=b=>c!=~d"),
+            [Ident, LessEqual, Ident, FatArrow, Ident, BangEqual, Tilde, Ident]
+        );
+        assert_eq!(kinds("===>"), [EqualEqual, FatArrow]);
+    }
+
+    #[test]
+    fn non_ascii_identifiers() {
+        assert_eq!(kinds("été λ"), [Ident, Ident]);
+    }
+
+    #[test]
+    fn unterminated_char_stops_after_one_character() {
+        assert_eq!(kinds("'a;"), [CharLiteral, Semi]);
+        assert_eq!(kinds(r"'\n);"), [CharLiteral, CloseParen, Semi]);
+        assert_eq!(kinds("'ab c'"), [CharLiteral, Ident, Ident, CharLiteral]);
+        assert_eq!(kinds(r"'ab' '\n\t';"), [CharLiteral, CharLiteral, Semi]);
+    }
+}
@@ -0,0 +1,130 @@
+//! Decoding of numeric literal tokens into the values they represent.
+//!
+//! Values are interned as [`Symbol`]s holding a canonical spelling, so that
+//! literals such as `0xFF` and `255` decode to the same value and later
+//! phases never need to look at the source text again.
+
+use crate::source::Symbol;
+
+/// Decode the text of an integer literal into its canonical decimal form.
+///
+/// Returns `None` for a malformed literal, which the lexer already reports.
+pub(crate) fn int(text: &str) -> Option<Symbol> {
+    let (radix, digits) = match text.get(..2) {
+        Some("0x") => (16, &text[2..]),
+        Some("0o") => (8, &text[2..]),
+        Some("0b") => (2, &text[2..]),
+        _ => (10, text),
+    };
+
+    // the value is kept as little-endian decimal digits, so literals of any
+    // size can be decoded without overflow
+    let mut decimal = Vec::<u8>::new();
+    let mut seen_digit = false;
+    for c in digits.chars().filter(|&c| c != '_') {
+        let mut carry = c.to_digit(radix)?;
+        seen_digit = true;
+        for digit in &mut decimal {
+            let value = u32::from(*digit) * radix + carry;
+            *digit = u8::try_from(value % 10).unwrap();
+            carry = value / 10;
+        }
+        while carry > 0 {
+            decimal.push(u8::try_from(carry % 10).unwrap());
+            carry /= 10;
+        }
+    }
+    if !seen_digit {
+        return None;
+    }
+    if decimal.is_empty() {
+        decimal.push(0);
+    }
+    let canonical = decimal.iter().rev().map(|&d| char::from(b'0' + d)).collect::<String>();
+    Some(Symbol::new(&canonical))
+}
+
+/// Negate a decoded numeric value, for folding a negation applied directly to
+/// a literal, such as `-123`, into a single constant.
+pub(crate) fn negate(value: Symbol) -> Symbol {
+    match value.as_str() {
+        "0" => value,
+        text => match text.strip_prefix('-') {
+            Some(positive) => Symbol::new(positive),
+            None => Symbol::new(&format!("-{text}")),
+        },
+    }
+}
+
+/// Decode the text of a float literal into its canonical form.
+///
+/// Returns `None` for a malformed literal, or one too large to be represented,
+/// both of which the lexer already reports.
+pub(crate) fn float(text: &str) -> Option<Symbol> {
+    let value = text.replace('_', "").parse::<f64>().ok().filter(|v| v.is_finite())?;
+    Some(Symbol::new(&format!("{value:?}")))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{float, int, negate};
+
+    #[test]
+    fn int_decimal() {
+        assert_eq!(int("0").unwrap().as_str(), "0");
+        assert_eq!(int("123").unwrap().as_str(), "123");
+        assert_eq!(int("1_000_000").unwrap().as_str(), "1000000");
+        assert_eq!(int("007").unwrap().as_str(), "7");
+    }
+
+    #[test]
+    fn int_radix() {
+        assert_eq!(int("0xFF").unwrap().as_str(), "255");
+        assert_eq!(int("0o17").unwrap().as_str(), "15");
+        assert_eq!(int("0b1010").unwrap().as_str(), "10");
+        assert_eq!(int("0x_0").unwrap().as_str(), "0");
+    }
+
+    #[test]
+    fn int_same_value_same_symbol() {
+        assert_eq!(int("0xff"), int("255"));
+        assert_eq!(int("0b1111_1111"), int("2_5_5"));
+    }
+
+    #[test]
+    fn int_larger_than_u128() {
+        assert_eq!(
+            int("0x1_0000_0000_0000_0000_0000_0000_0000_0000").unwrap().as_str(),
+            "340282366920938463463374607431768211456",
+        );
+    }
+
+    #[test]
+    fn int_malformed() {
+        assert_eq!(int("0b102"), None);
+        assert_eq!(int("0x"), None);
+        assert_eq!(int("12a3"), None);
+    }
+
+    #[test]
+    fn negate_values() {
+        assert_eq!(negate(int("123").unwrap()).as_str(), "-123");
+        assert_eq!(negate(negate(int("0x10").unwrap())).as_str(), "16");
+        assert_eq!(negate(int("0").unwrap()).as_str(), "0");
+        assert_eq!(negate(float("2.5").unwrap()).as_str(), "-2.5");
+    }
+
+    #[test]
+    fn float_canonical() {
+        assert_eq!(float("1.5").unwrap().as_str(), "1.5");
+        assert_eq!(float("1_000.0").unwrap().as_str(), "1000.0");
+        assert_eq!(float("2.5e-3").unwrap().as_str(), "0.0025");
+        assert_eq!(float("1E3"), float("1000.0"));
+    }
+
+    #[test]
+    fn float_malformed() {
+        assert_eq!(float("1.0e+"), None);
+        assert_eq!(float("1e400"), None);
+    }
+}
@@ -1,8 +1,11 @@
-mod ast;
+#[expect(dead_code)]
+pub(crate) mod ast;
 mod grammar;
 mod kind;
 mod lexer;
+pub(crate) mod literal;
 mod parser;
+mod reparse;
 mod tree;
 
 #[salsa::tracked]
@@ -10,7 +13,162 @@ pub(crate) fn parse(
     db: &dyn crate::Db,
     file: crate::source::File,
 ) -> (tree::Tree, Vec<crate::diagnostic::Diagnostic>) {
-    let mut parser = parser::Parser::new(file, file.text(db));
+    match file.previous(db) {
+        Some((previous, edit)) => reparse::reparse(db, file, &parse(db, previous), edit),
+        None => parse_text(file, file.text(db)),
+    }
+}
+
+fn parse_text(
+    file: crate::source::File,
+    text: &str,
+) -> (tree::Tree, Vec<crate::diagnostic::Diagnostic>) {
+    let mut parser = parser::Parser::new(file, text);
     grammar::root(&mut parser);
     parser.finish()
 }
+
+/// A file holding `text`, for a test.
+#[cfg(test)]
+fn test_file(db: &dyn crate::Db, text: &str) -> crate::source::File {
+    crate::source::File::new(db, "<test>".into(), text.to_owned(), None)
+}
+
+/// The tree of `text`, for a test which doesn't look at the diagnostics.
+#[cfg(test)]
+fn test_tree(text: &str) -> tree::Tree {
+    let db = &crate::Database::default();
+    parse(db, test_file(db, text)).0
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::source::Span;
+    use proptest::prelude::*;
+
+    /// Pieces of source text, which are combined into inputs that reach deeper
+    /// into the grammar than arbitrary text.
+    const FRAGMENTS: &[&str] = &[
+        " ", "\n", "// a\n", "/* a */", "/* ", "fn", "type", "trait", "mod", "use", "pub", "let",
+        "if", "else", "match", "with", "a", "Int", "Fn", "List", "1", "2.5", "true", "\"a\"",
+        "'a'", "(", ")", "[", "]", "{", "}", ".", ",", ":", ";", "=", "==", "->", "=>", "|", "&",
+        "~", "_", "+", "-", "*", "/", "<", "§",
+    ];
+
+    /// Programs without syntax errors, which are edited into inputs that are
+    /// mostly well-formed.
+    const PROGRAMS: &[&str] = &[
+        "pub fn add(a: Int, b: Int) -> Int = a + b * 2;",
+        "type Optional(T) = Some(T) | None;\ntype Point = { x: Int, y: Int };",
+        "trait Equals(Right) with Right = [fn equals(left: Self, right: Right) -> Boolean];",
+        "mod inner { use std.prelude.[Optional, map]; }",
+        "fn foo(xs: List(Int)) = { let a = xs[0]; if a < 1 { -a } else { a } };",
+        "fn bar(o: Optional(Int)) = match o { Some(1 | 2) => 0, _ => map(o, |x| x.y) };",
+    ];
+
+    fn check(input: &str) {
+        let db = &crate::Database::default();
+        let (tree, diagnostics) = super::parse(db, super::test_file(db, input));
+        tree.check_invariants(input);
+        assert_eq!(tree.text(), input);
+        for diagnostic in &diagnostics {
+            assert!(
+                !diagnostic.message.starts_with("internal compiler error"),
+                "{}",
+                diagnostic.message
+            );
+        }
+    }
+
+    #[test]
+    fn programs_are_well_formed() {
+        let db = &crate::Database::default();
+        for &program in PROGRAMS {
+            assert!(super::parse(db, super::test_file(db, program)).1.is_empty(), "{program}");
+        }
+    }
+
+    #[test]
+    fn std_is_well_formed() {
+        let db = &crate::Database::default();
+        let files = [
+            include_str!("../../std/src/boolean.fury"),
+            include_str!("../../std/src/comparison.fury"),
+            include_str!("../../std/src/lib.fury"),
+            include_str!("../../std/src/num.fury"),
+            include_str!("../../std/src/operators.fury"),
+            include_str!("../../std/src/optional.fury"),
+            include_str!("../../std/src/prelude.fury"),
+        ];
+        for text in files {
+            assert!(super::parse(db, super::test_file(db, text)).1.is_empty(), "{text}");
+        }
+    }
+
+    fn fragments() -> impl Strategy<Value = String> {
+        prop::collection::vec(prop::sample::select(FRAGMENTS), 0..64)
+            .prop_map(|fragments| fragments.concat())
+    }
+
+    /// An edit replacing up to a few characters at some point in a text with
+    /// a fragment.
+    fn edit() -> impl Strategy<Value = (prop::sample::Index, usize, &'static str)> {
+        (any::<prop::sample::Index>(), 0..8_usize, prop::sample::select(FRAGMENTS))
+    }
+
+    /// The span of `text` which an edit from [`edit`] replaces.
+    fn edit_span(text: &str, (start, deleted, _): &(prop::sample::Index, usize, &str)) -> Span {
+        let mut start = start.index(text.len() + 1);
+        let mut end = (start + deleted).min(text.len());
+        while !text.is_char_boundary(start) {
+            start -= 1;
+        }
+        while !text.is_char_boundary(end) {
+            end += 1;
+        }
+        Span::new(u32::try_from(start).unwrap(), u32::try_from(end).unwrap())
+    }
+
+    fn edited_program() -> impl Strategy<Value = String> {
+        (prop::sample::select(PROGRAMS), prop::collection::vec(edit(), 1..4)).prop_map(
+            |(program, edits)| {
+                let mut text = program.to_owned();
+                for edit in edits {
+                    let span = edit_span(&text, &edit);
+                    text.replace_range(
+                        usize::try_from(span.start).unwrap()..usize::try_from(span.end).unwrap(),
+                        edit.2,
+                    );
+                }
+                text
+            },
+        )
+    }
+
+    proptest! {
+        #[test]
+        fn arbitrary_text(input in any::<String>()) {
+            check(&input);
+        }
+
+        #[test]
+        fn fragment_sequences(input in fragments()) {
+            check(&input);
+        }
+
+        #[test]
+        fn edited_programs(input in edited_program()) {
+            check(&input);
+        }
+
+        #[test]
+        fn reparse_programs(input in prop::sample::select(PROGRAMS), edit in edit()) {
+            super::reparse::tests::check(input, edit_span(input, &edit), edit.2);
+        }
+
+        #[test]
+        fn reparse_edited_programs(input in edited_program(), edit in edit()) {
+            super::reparse::tests::check(&input, edit_span(&input, &edit), edit.2);
+        }
+    }
+}
@@ -8,8 +8,16 @@ const FUEL_CAPACITY: u32 = 256;
 pub(super) struct Parser<'text> {
     file: crate::source::File,
     text: &'text str,
-    lexer: core::iter::Peekable<super::lexer::Lexer<'text>>,
+    lexer: super::lexer::Lexer<'text>,
+    lookahead: std::collections::VecDeque<super::lexer::Token>,
     fuel: core::cell::Cell<u32>,
+    previous_span: crate::source::Span,
+    unexpected_span: Option<crate::source::Span>,
+    stuck: bool,
+    /// Whether a rule was stuck at the end of the input, after which the
+    /// parser stops checking for progress, and each rule is left to finish by
+    /// itself, since every loop in the grammar stops at the end of the input.
+    abandoned: bool,
     events: Vec<Event>,
     diagnostics: Vec<crate::diagnostic::Diagnostic>,
 }
@@ -19,8 +27,13 @@ impl<'text> Parser<'text> {
         Self {
             file,
             text,
-            lexer: super::lexer::Lexer::new(text).peekable(),
+            lexer: super::lexer::Lexer::new(file, text),
+            lookahead: std::collections::VecDeque::new(),
             fuel: std::cell::Cell::new(FUEL_CAPACITY),
+            previous_span: crate::source::Span::new(0, 0),
+            unexpected_span: None,
+            stuck: false,
+            abandoned: false,
             events: Vec::new(),
             diagnostics: Vec::new(),
         }
@@ -31,11 +44,48 @@ impl<'text> Parser<'text> {
     }
 
     pub(super) fn at_end(&mut self) -> bool {
-        self.lexer.peek().is_none()
+        self.peek_token().is_none()
+    }
+
+    /// Lex another token into the lookahead buffer, returning `false` at the
+    /// end of the input.
+    fn lex_token(&mut self) -> bool {
+        let token = self.lexer.next();
+        self.diagnostics.extend(self.lexer.take_diagnostics());
+        token.map(|t| self.lookahead.push_back(t)).is_some()
     }
 
     fn peek_token(&mut self) -> Option<super::lexer::Token> {
-        self.lexer.peek().copied()
+        if self.lookahead.is_empty() {
+            self.lex_token();
+        }
+        self.lookahead.front().copied()
+    }
+
+    fn next_token(&mut self) -> Option<super::lexer::Token> {
+        self.peek_token();
+        self.lookahead.pop_front()
+    }
+
+    /// The kinds of the upcoming non-trivia tokens, starting with the one
+    /// [`Parser::peek`] would return, without consuming them.
+    ///
+    /// Unlike `peek`, this does not use any fuel, so a scan with it must stop
+    /// by itself, at the latest at the end of the input.
+    pub(super) fn lookahead(&mut self) -> impl Iterator<Item = Kind> {
+        let mut index = 0;
+        core::iter::from_fn(move || {
+            loop {
+                if index == self.lookahead.len() && !self.lex_token() {
+                    return None;
+                }
+                let kind = self.lookahead[index].kind;
+                index += 1;
+                if !kind.is_trivia() {
+                    return Some(kind);
+                }
+            }
+        })
     }
 
     pub(super) fn peek_span(&mut self) -> crate::source::Span {
@@ -48,25 +98,75 @@ impl<'text> Parser<'text> {
         )
     }
 
+    /// The span of the last non-trivia token that was consumed.
+    pub(super) fn previous_span(&self) -> crate::source::Span {
+        self.previous_span
+    }
+
+    #[track_caller]
     pub(super) fn peek(&mut self) -> Kind {
-        assert!(self.fuel.get() != 0, "parser is stuck");
-        self.fuel.set(self.fuel.get() - 1);
+        if !self.abandoned {
+            if self.fuel.get() == 0 {
+                self.unstick(core::panic::Location::caller());
+            }
+            self.fuel.set(self.fuel.get() - 1);
+        }
         self.peek_token().map_or(Kind::Eof, |t| t.kind)
     }
 
+    /// Get out of a grammar rule which keeps looking at the next token without
+    /// consuming it, by consuming it regardless. This is a bug in the grammar,
+    /// but one which shouldn't take down the whole compiler.
+    ///
+    /// At the end of the input there is nothing left to consume, so the parser
+    /// is abandoned instead, and only ever finds the end of the input from then
+    /// on.
+    #[cold]
+    fn unstick(&mut self, location: &core::panic::Location<'_>) {
+        // a rule which is stuck once is likely to get stuck again, so only the
+        // first time is reported
+        if !self.stuck {
+            self.stuck = true;
+            let found = self.peek_token().map_or(Kind::Eof, |t| t.kind);
+            let span = self.peek_span();
+            self.diagnostic(errors::stuck(self.file, span, found, location));
+        }
+        self.fuel.set(FUEL_CAPACITY);
+        if self.at_end() {
+            self.abandoned = true;
+            return;
+        }
+        let m = self.open();
+        self.bump();
+        self.close(m, Kind::Error);
+    }
+
+    /// Whether the next token is an identifier spelled `keyword`, which only
+    /// acts as a keyword in some positions.
+    #[track_caller]
+    pub(super) fn at_contextual_keyword(&mut self, keyword: &str) -> bool {
+        let span = self.peek_span();
+        self.peek() == Kind::Ident && &self.text[span] == keyword
+    }
+
     fn bump_raw(&mut self) {
         self.fuel.set(FUEL_CAPACITY);
-        let Some(super::lexer::Token { kind, span }) = self.lexer.next() else {
+        let Some(super::lexer::Token { kind, span }) = self.next_token() else {
             panic!("tried to consume nonexistent token");
         };
+        if !kind.is_trivia() {
+            self.previous_span = span;
+        }
         self.events.push(Event::Token { kind, length: span.length() });
     }
 
+    #[track_caller]
     pub(super) fn bump(&mut self) {
         self.bump_raw();
         self.eat_trivia();
     }
 
+    #[track_caller]
     pub(super) fn eat_trivia(&mut self) {
         while self.peek().is_trivia() {
             self.bump_raw();
@@ -79,27 +179,64 @@ impl<'text> Parser<'text> {
         mark
     }
 
-    pub(super) fn close(&mut self, marker: MarkOpened, kind: Kind) {
-        let num_trivia_before = self
-            .events
-            .iter()
-            .rev()
-            .take_while(|e| matches!(e, Event::Token { kind, ..} if kind.is_trivia()))
-            .count();
+    /// Begin a new node which will contain an already-closed node, for
+    /// example, the left-hand side of a binary expression.
+    pub(super) fn open_before(&mut self, marker: MarkClosed) -> MarkOpened {
+        self.events.insert(marker.index, Event::Open { kind: Kind::Error });
+        MarkOpened { index: marker.index }
+    }
+
+    pub(super) fn close(&mut self, marker: MarkOpened, kind: Kind) -> MarkClosed {
+        // trailing trivia belongs to the enclosing node, except for the root,
+        // which has to contain every token
+        let num_trivia_before = match kind {
+            Kind::Root => 0,
+            _ => self
+                .events
+                .iter()
+                .rev()
+                .take_while(|e| matches!(e, Event::Token { kind, .. } if kind.is_trivia()))
+                .count(),
+        };
         self.events[marker.index] = Event::Open { kind };
         self.events.insert(self.events.len() - num_trivia_before, Event::Close);
+        MarkClosed { index: marker.index }
     }
 
+    #[track_caller]
     pub(super) fn expect(&mut self, expected: Kind) {
         let found = self.peek();
         if found == expected {
             self.bump();
         } else {
-            let span = self.peek_span();
-            self.diagnostic(errors::unexpected_token(self.file, span, &[expected], None, found));
+            self.unexpected(found, &[expected], None);
         }
     }
 
+    /// Report that the next token, `found`, is not one of `expected`, unless
+    /// that token has already been reported, since the parser not having
+    /// recovered yet is most likely the cause of the second error.
+    pub(super) fn unexpected(
+        &mut self,
+        found: Kind,
+        expected: &[Kind],
+        expected_phrase: Option<&'static str>,
+    ) {
+        let span = self.peek_span();
+        if self.unexpected_span == Some(span) {
+            return;
+        }
+        self.unexpected_span = Some(span);
+        self.diagnostic(errors::unexpected_token(
+            self.file,
+            span,
+            expected,
+            expected_phrase,
+            found,
+        ));
+    }
+
+    #[track_caller]
     pub(super) fn optional(&mut self, kind: Kind) -> bool {
         if self.peek() == kind {
             self.bump();
@@ -109,25 +246,145 @@ impl<'text> Parser<'text> {
         }
     }
 
+    /// Skip tokens up to the next one in `recovery`, or the end of the input,
+    /// so that a mistake which has already been reported isn't reported again
+    /// at every token which follows it.
+    ///
+    /// A group in brackets is skipped as a whole, so that the tokens in it,
+    /// like the `,` in `(a, b)`, don't end the mistake early. A group which
+    /// isn't closed ends at the first token which can't be directly inside it.
+    #[track_caller]
+    pub(super) fn skip_until(&mut self, recovery: &[Kind]) {
+        // the delimiters which close the groups skipped into so far
+        let mut closing = Vec::new();
+        loop {
+            let kind = self.peek();
+            if kind == Kind::Eof || closing.is_empty() && recovery.contains(&kind) {
+                return;
+            }
+            match kind {
+                Kind::OpenParen => closing.push(Kind::CloseParen),
+                Kind::OpenBracket => closing.push(Kind::CloseBracket),
+                Kind::OpenBrace => closing.push(Kind::CloseBrace),
+                Kind::CloseParen | Kind::CloseBracket | Kind::CloseBrace => {
+                    // a delimiter which closes an outer group also closes the
+                    // groups inside it, and one which closes no group at all
+                    // belongs to what encloses the mistake
+                    if let Some(index) = closing.iter().rposition(|&c| c == kind) {
+                        closing.truncate(index);
+                    } else if !closing.is_empty() {
+                        closing.clear();
+                        continue;
+                    }
+                }
+                _ if closing.last().is_some_and(|&c| !can_be_in_group(c, kind)) => {
+                    closing.clear();
+                    continue;
+                }
+                _ => {}
+            }
+            self.bump();
+        }
+    }
+
+    /// Like [`Parser::skip_until`], but wrapping the skipped tokens in an
+    /// `Error` node, if there are any.
+    #[track_caller]
+    pub(super) fn recover(&mut self, recovery: &[Kind]) {
+        if self.at_end() || recovery.contains(&self.peek()) {
+            return;
+        }
+        let m = self.open();
+        self.skip_until(recovery);
+        self.close(m, Kind::Error);
+    }
+
     pub(super) fn diagnostic(&mut self, diagnostic: crate::diagnostic::Diagnostic) {
         self.diagnostics.push(diagnostic);
     }
 
     pub(super) fn finish(self) -> (Tree, Vec<crate::diagnostic::Diagnostic>) {
-        let Self { events, diagnostics, .. } = self;
-        let mut builder = Builder::new();
-        for event in events {
-            match event {
-                Event::Open { kind } => builder.open(kind),
-                Event::Close => builder.close(),
-                Event::Token { kind, length } => builder.token(kind, length),
+        let text = self.text;
+        let (events, diagnostics) = self.finish_events();
+        (build(text, events), diagnostics)
+    }
+
+    /// Like [`Parser::finish`], but returning the events which describe the
+    /// tree, so that they can be spliced into another tree.
+    pub(super) fn finish_events(self) -> (Vec<Event>, Vec<crate::diagnostic::Diagnostic>) {
+        let Self { mut events, mut diagnostics, .. } = self;
+        remove_empty_nodes(&mut events);
+        sort_diagnostics(&mut diagnostics);
+        (events, diagnostics)
+    }
+}
+
+/// Whether `kind` can be directly inside a group closed by `closing`. Only a
+/// block has statements, and the only items in a group are the methods in the
+/// brackets of a trait, since the braces of an item list are never skipped
+/// into without skipping past the start of its item.
+fn can_be_in_group(closing: Kind, kind: Kind) -> bool {
+    match kind {
+        Kind::Semi => closing == Kind::CloseBrace,
+        Kind::FnKw => closing == Kind::CloseBracket,
+        Kind::PubKw | Kind::TypeKw | Kind::TraitKw | Kind::ModKw | Kind::UseKw => false,
+        _ => true,
+    }
+}
+
+/// Sort diagnostics by where they start, rather than by when the lexer or the
+/// parser came across them, so that the order doesn't depend on how far ahead
+/// the parser happened to look.
+pub(super) fn sort_diagnostics(diagnostics: &mut [crate::diagnostic::Diagnostic]) {
+    diagnostics.sort_by_key(|diagnostic| diagnostic.primary.as_ref().map(|label| label.span.start));
+}
+
+/// Build a tree of `text` from the events which describe it.
+pub(super) fn build(text: &str, events: impl IntoIterator<Item = Event>) -> Tree {
+    let mut builder = Builder::new(text);
+    for event in events {
+        match event {
+            Event::Open { kind } => builder.open(kind),
+            Event::Close => builder.close(),
+            Event::Token { kind, length } => builder.token(kind, length),
+        }
+    }
+    builder.build()
+}
+
+/// Remove the nodes which contain no tokens, such as a placeholder for a
+/// missing expression, so that every leaf of the tree is a token. The root is
+/// kept, since even an empty file has one.
+fn remove_empty_nodes(events: &mut Vec<Event>) {
+    let mut keep = vec![true; events.len()];
+    // the index of each open node's `Open` event, and whether it has a token
+    let mut open = Vec::<(usize, bool)>::new();
+    for (index, event) in events.iter().enumerate() {
+        match event {
+            Event::Open { .. } => open.push((index, false)),
+            Event::Close => {
+                let (start, has_token) = open.pop().expect("close event with no open node");
+                match open.last_mut() {
+                    Some(parent) if has_token => parent.1 = true,
+                    Some(_) => {
+                        keep[start] = false;
+                        keep[index] = false;
+                    }
+                    None => {}
+                }
+            }
+            Event::Token { .. } => {
+                if let Some(parent) = open.last_mut() {
+                    parent.1 = true;
+                }
             }
         }
-        (builder.build(), diagnostics)
     }
+    let mut keep = keep.into_iter();
+    events.retain(|_| keep.next().unwrap());
 }
 
-enum Event {
+pub(super) enum Event {
     Open { kind: Kind },
     Close,
     Token { kind: Kind, length: u32 },
@@ -139,20 +396,19 @@ pub(super) struct MarkOpened {
     index: usize,
 }
 
+#[derive(Clone, Copy)]
+pub(super) struct MarkClosed {
+    index: usize,
+}
+
 macro_rules! expected {
-    ($parser:expr, $found:expr $(, $matcher:tt)+ $(,)? $(($phrase:literal))?) => {{
-        let span = $parser.peek_span();
-        let expected = &[$(crate::syntax::kind::t![$matcher]),+];
-        $parser.diagnostic(
-            $crate::syntax::parser::errors::unexpected_token(
-                $parser.file(),
-                span,
-                expected,
-                crate::syntax::parser::expected!(@phrase $($phrase)?),
-                $found,
-            )
-        );
-    }};
+    ($parser:expr, $found:expr $(, $matcher:tt)+ $(,)? $(($phrase:literal))?) => {
+        $parser.unexpected(
+            $found,
+            &[$(crate::syntax::kind::t![$matcher]),+],
+            crate::syntax::parser::expected!(@phrase $($phrase)?),
+        )
+    };
     (@phrase $phrase:literal) => { Some($phrase) };
     (@phrase) => { None };
 }
@@ -182,6 +438,26 @@ pub(super) mod errors {
         diagnostic
     }
 
+    pub(in super::super) fn stuck(
+        file: crate::source::File,
+        span: crate::source::Span,
+        found: crate::syntax::kind::Kind,
+        location: &core::panic::Location<'_>,
+    ) -> crate::diagnostic::Diagnostic {
+        let mut diagnostic = crate::diagnostic::Diagnostic::error(
+            "internal compiler error: the parser stopped making progress",
+        );
+        diagnostic.primary(file, span, format!("stuck at {found} here"));
+        diagnostic.note(format!("the parser was stuck in the grammar at {location}"));
+        diagnostic.note("this is a bug in the compiler, so please report it");
+        if found == crate::syntax::kind::Kind::Eof {
+            diagnostic.note("nothing was skipped, since the input had ended");
+        } else {
+            diagnostic.note("the token was skipped, so later errors may be inaccurate");
+        }
+        diagnostic
+    }
+
     fn list_format<T: core::fmt::Display>(list: &[T]) -> String {
         match list.len() {
             0 => panic!("attempted to create a list of 0 items"),
@@ -198,3 +474,61 @@ pub(super) mod errors {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::Parser;
+    use crate::syntax::kind::Kind;
+
+    #[test]
+    fn stuck_rule_is_reported_once_and_skipped() {
+        let db = &crate::Database::default();
+        let file = crate::syntax::test_file(db, "a b");
+        let mut parser = Parser::new(file, file.text(db));
+        let m = parser.open();
+        // a rule which never consumes the token it is looking at
+        while parser.peek() != Kind::Eof {}
+        parser.close(m, Kind::Root);
+        let (tree, diagnostics) = parser.finish();
+
+        let mut output = String::new();
+        tree.debug(&mut output).unwrap();
+        assert_eq!(
+            output,
+            "Root@0..3\n  Error@0..1\n    Ident@0..1 \"a\"\n  Whitespace@1..2 \" \"\n  Error@2..3\n    Ident@2..3 \"b\"\n"
+        );
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(
+            diagnostics[0].message,
+            "internal compiler error: the parser stopped making progress"
+        );
+        assert!(diagnostics[0].notes[0].contains(file!()));
+    }
+
+    #[test]
+    fn stuck_rule_at_end_is_abandoned() {
+        let db = &crate::Database::default();
+        let file = crate::syntax::test_file(db, "a");
+        let mut parser = Parser::new(file, file.text(db));
+        let m = parser.open();
+        parser.bump();
+        // a rule which keeps looking at the end of the input, but which stops
+        // there eventually, like every loop in the grammar
+        for _ in 0..1000 {
+            assert_eq!(parser.peek(), Kind::Eof);
+        }
+        parser.close(m, Kind::Root);
+        let (tree, diagnostics) = parser.finish();
+
+        let mut output = String::new();
+        tree.debug(&mut output).unwrap();
+        assert_eq!(output, "Root@0..1\n  Ident@0..1 \"a\"\n");
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(
+            diagnostics[0].message,
+            "internal compiler error: the parser stopped making progress"
+        );
+        assert!(diagnostics[0].notes[0].contains(file!()));
+        assert_eq!(diagnostics[0].notes[2], "nothing was skipped, since the input had ended");
+    }
+}
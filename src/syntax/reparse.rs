@@ -0,0 +1,266 @@
+//! Parsing a file again after an edit, by parsing only the item or block which
+//! contains the edit and splicing it into the old tree.
+
+use super::{
+    parser::{Event, Parser},
+    tree::{TokenAtOffset, Tree},
+};
+use crate::{diagnostic::Diagnostic, source::Span};
+
+/// Parse `file` again after its text was edited by replacing `edit`, a span of
+/// its old text, where `old` is the result of parsing the old text, which may
+/// have been in another file.
+///
+/// If the edit is within an item or block, only the smallest such node is
+/// parsed again, and its diagnostics replace the old ones within it. Otherwise,
+/// or if the node might have been parsed differently as part of the whole
+/// file, the whole file is parsed again. That includes when a diagnostic is
+/// reported at the token just after the node, since it can't be told whether
+/// the node or what follows it reported that.
+pub(crate) fn reparse(
+    db: &dyn crate::Db,
+    file: crate::source::File,
+    old: &(Tree, Vec<Diagnostic>),
+    edit: Span,
+) -> (Tree, Vec<Diagnostic>) {
+    let text = file.text(db);
+    reparse_node(file, text, old, edit).unwrap_or_else(|| super::parse_text(file, text))
+}
+
+fn reparse_node(
+    file: crate::source::File,
+    text: &str,
+    (tree, diagnostics): &(Tree, Vec<Diagnostic>),
+    edit: Span,
+) -> Option<(Tree, Vec<Diagnostic>)> {
+    let root = tree.root();
+    let old_length = root.span().end;
+    let delta = i64::from(u32::try_from(text.len()).unwrap()) - i64::from(old_length);
+
+    // the first and last tokens of the node must be left alone, so that the
+    // parser still knows where the node starts and ends
+    let (node, reparser) = root.covering_element(edit).ancestors().find_map(|node| {
+        let span = node.span();
+        let first = node.token_at_offset(span.start).right_biased()?.span();
+        let last = node.token_at_offset(span.end).left_biased()?.span();
+        let reparser = super::grammar::reparser(node.kind(), node.parent()?.kind())?;
+        (first.end <= edit.start && edit.end <= last.start).then_some((node, reparser))
+    })?;
+    let old_span = node.span();
+    let span = Span::new(old_span.start, shift(old_span.end, delta));
+
+    // a token is lexed up to a character or two past its end, so the edit
+    // could still change where the token before the node or its first token
+    // ends, such as by merging them with what follows
+    let first = node.token_at_offset(old_span.start).right_biased()?;
+    let previous = match root.token_at_offset(old_span.start) {
+        TokenAtOffset::Between(previous, _) => Some(previous),
+        _ => None,
+    };
+    for token in previous.into_iter().chain([first]) {
+        let token_span = token.span();
+        let rest = Span::new(token_span.start, u32::try_from(text.len()).unwrap());
+        let lexed = super::lexer::Lexer::new(file, &text[rest]).next()?;
+        if lexed.kind != token.kind() || lexed.span.length() != token_span.length() {
+            return None;
+        }
+    }
+
+    // the rule which parsed the node may also have reported the token after
+    // it, such as a missing `;`, so diagnostics there can't be told apart from
+    // those reported by what surrounds the node
+    let next = next_token(file, text, span.end);
+    let old_next = Span::new(shift(next.start, -delta), shift(next.end, -delta));
+
+    // the diagnostics inside the node are replaced by those from parsing it
+    // again
+    let mut kept = Vec::with_capacity(diagnostics.len());
+    for diagnostic in diagnostics {
+        let mut diagnostic = diagnostic.clone();
+        if let Some(label) = &diagnostic.primary {
+            if old_span.start < label.span.start && label.span.end < old_span.end {
+                continue;
+            }
+            // a diagnostic overlapping the edge of the node may have come from
+            // parsing what surrounds it
+            if label.span.start < old_span.end && label.span.end > old_span.start
+                || touches(label.span, old_next)
+            {
+                return None;
+            }
+        }
+        for label in diagnostic.primary.iter_mut().chain(&mut diagnostic.secondary) {
+            label.file = file;
+            if label.span.start >= old_span.end {
+                label.span =
+                    Span::new(shift(label.span.start, delta), shift(label.span.end, delta));
+            }
+        }
+        kept.push(diagnostic);
+    }
+
+    // the parser is given the rest of the file, so that it sees the same tokens
+    // after the node as when parsing the whole file
+    let rest = &text[Span::new(span.start, u32::try_from(text.len()).unwrap())];
+    let mut parser = Parser::new(file, rest);
+    reparser(&mut parser);
+    let (mut events, new_diagnostics) = parser.finish_events();
+    let end = node_end(&events, span.length())?;
+    events.truncate(end);
+
+    // what was reported further after the node comes from lexing ahead, and
+    // has already been reported
+    for mut diagnostic in new_diagnostics {
+        for label in diagnostic.primary.iter_mut().chain(&mut diagnostic.secondary) {
+            label.span = Span::new(label.span.start + span.start, label.span.end + span.start);
+        }
+        match &diagnostic.primary {
+            Some(label) if touches(label.span, next) => return None,
+            Some(label) if label.span.end > span.end => {}
+            _ => kept.push(diagnostic),
+        }
+    }
+    super::parser::sort_diagnostics(&mut kept);
+
+    let replacement = super::parser::build(rest, events);
+    Some((node.replace_with(replacement.root().green().clone()), kept))
+}
+
+fn shift(offset: u32, delta: i64) -> u32 {
+    u32::try_from(i64::from(offset) + delta).unwrap()
+}
+
+/// The span of the first token of `text` at or after `offset` which isn't
+/// trivia, or an empty span at the end of the text.
+fn next_token(file: crate::source::File, text: &str, offset: u32) -> Span {
+    let length = u32::try_from(text.len()).unwrap();
+    let token = super::lexer::Lexer::new(file, &text[Span::new(offset, length)])
+        .find(|token| !token.kind.is_trivia());
+    token.map_or(Span::new(length, length), |token| {
+        Span::new(offset + token.span.start, offset + token.span.end)
+    })
+}
+
+/// Whether two spans overlap, counting an empty span as overlapping a span
+/// which it is at either end of.
+fn touches(a: Span, b: Span) -> bool {
+    let empty = a.start == a.end || b.start == b.end;
+    a.start < b.end && b.start < a.end || empty && a.start <= b.end && b.start <= a.end
+}
+
+/// The index just after the event which closes the node that the first event
+/// opens, if the node is `length` bytes long.
+fn node_end(events: &[Event], length: u32) -> Option<usize> {
+    let mut depth = 0_usize;
+    let mut node_length = 0;
+    for (index, event) in events.iter().enumerate() {
+        match event {
+            Event::Open { .. } => depth += 1,
+            Event::Close => {
+                depth -= 1;
+                if depth == 0 {
+                    return (node_length == length).then_some(index + 1);
+                }
+            }
+            Event::Token { length, .. } => node_length += length,
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+pub(super) mod tests {
+    use crate::source::Span;
+
+    /// Edit `before` by replacing `edit` with `inserted`, returning whether
+    /// only a node was parsed again, after checking that the result is the
+    /// same as parsing the whole file.
+    pub(in super::super) fn check(before: &str, edit: Span, inserted: &str) -> bool {
+        let db = &crate::Database::default();
+        let previous = crate::syntax::test_file(db, before);
+        let old = crate::syntax::parse(db, previous);
+        let mut after = before.to_owned();
+        after.replace_range(
+            usize::try_from(edit.start).unwrap()..usize::try_from(edit.end).unwrap(),
+            inserted,
+        );
+        let file =
+            crate::source::File::new(db, "<test>".into(), after.clone(), Some((previous, edit)));
+
+        let reparsed = super::reparse_node(file, &after, &old, edit);
+        let expected = crate::syntax::parse_text(file, &after);
+        assert_eq!(crate::syntax::parse(db, file), expected);
+        if let Some(reparsed) = &reparsed {
+            assert_eq!(reparsed, &expected);
+        }
+        reparsed.is_some()
+    }
+
+    #[test]
+    fn edit_in_block() {
+        let before = "fn foo() = { let a = 1; a };\nfn bar() = 2;";
+        assert!(check(before, Span::new(21, 22), "2 + 3"));
+    }
+
+    #[test]
+    fn edit_in_nested_block() {
+        let before = "fn foo() = { let a = { 1 }; a };";
+        assert!(check(before, Span::new(24, 24), " + b"));
+    }
+
+    #[test]
+    fn edit_in_item() {
+        let before = "fn foo() = 1;\nfn bar(a: Int) = a;\nfn baz() = 3;";
+        assert!(check(before, Span::new(24, 27), "List(Int), b: Int"));
+    }
+
+    #[test]
+    fn edit_in_mod_item() {
+        let before = "mod inner { fn foo() = 1; }";
+        assert!(check(before, Span::new(23, 24), "foo()"));
+    }
+
+    #[test]
+    fn edit_fixing_error() {
+        let before = "fn foo() = { let = 1; 2 };\nfn bar() = 2;";
+        assert!(check(before, Span::new(17, 17), "a "));
+    }
+
+    #[test]
+    fn edit_at_node_edge() {
+        let before = "fn foo() = 1;\nfn bar() = 2;";
+        assert!(!check(before, Span::new(12, 13), ""));
+        assert!(!check(before, Span::new(13, 14), "fn"));
+    }
+
+    #[test]
+    fn edit_causing_error() {
+        let before = "fn foo() = { let a = 1; a };\nfn bar() = 2;";
+        assert!(check(before, Span::new(22, 23), ""));
+    }
+
+    #[test]
+    fn edit_keeping_error() {
+        let before = "fn foo() = { let = 1; 2 };\nfn bar() = { 3 4 };";
+        assert!(check(before, Span::new(22, 23), "5"));
+        assert!(check(before, Span::new(44, 44), " + 5"));
+    }
+
+    #[test]
+    fn edit_with_error_after_node() {
+        let before = "fn foo(a) = 1\nfn bar() = 2;";
+        assert!(!check(before, Span::new(7, 8), "b"));
+    }
+
+    #[test]
+    fn edit_in_trait_method() {
+        let before = "trait Equals = [fn equals(left: Self) -> Boolean];\nfn foo() = 1;";
+        assert!(check(before, Span::new(26, 30), "l"));
+    }
+
+    #[test]
+    fn edit_unterminated_comment() {
+        let before = "fn foo() = { 1 };\nfn bar() = 2;";
+        assert!(!check(before, Span::new(14, 14), "/*"));
+    }
+}
@@ -1,192 +1,519 @@
 //! A representation of a concrete, untyped syntax tree.
+//!
+//! The tree has two layers. The green layer is made of immutable nodes which
+//! know their kind, their children, and their length, but not where they are.
+//! This lets syntax which appears more than once share a node, and lets a tree
+//! share every node outside an edit with the tree from before the edit, so two
+//! trees compare equal when they hold the same syntax, wherever it is.
+//!
+//! The red layer is made of [`Node`]s, which are cursors into the green layer
+//! that compute the offset and parent of a node on demand, as the tree is
+//! walked.
 
 use super::kind::Kind;
 use crate::source::Span;
+use std::{collections::HashMap, rc::Rc, sync::Arc};
 
 /// A complete syntax tree. This is meant to be constructed using a [`Builder`].
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub(crate) struct Tree {
-    /// An arena containing all nodes in the tree contiguously.
-    nodes: NodeArena,
-    /// A reference to the tree's lone root node.
-    root: Option<NodeKey>,
+    /// The tree's lone root node.
+    root: Arc<GreenNode>,
 }
 
 impl Tree {
-    /// Construct an empty tree.
-    const fn new() -> Self {
-        Self { nodes: NodeArena::new(), root: None }
+    /// The root node of the tree.
+    pub(crate) fn root(&self) -> Node {
+        Node(Rc::new(NodeData {
+            green: GreenElement::Node(Arc::clone(&self.root)),
+            parent: None,
+            index: 0,
+            offset: 0,
+        }))
+    }
+
+    /// Reconstruct the text that the tree was parsed from. Since every token,
+    /// including trivia, is in the tree, nothing is lost.
+    #[cfg_attr(not(test), expect(dead_code))]
+    pub(crate) fn text(&self) -> String {
+        self.root().text()
+    }
+
+    /// Check that the tree is well-formed for `input`: it has the same text,
+    /// the children of a node cover its span contiguously, and only tokens are
+    /// leaves.
+    #[cfg(test)]
+    pub(super) fn check_invariants(&self, input: &str) {
+        let root = self.root();
+        assert_eq!(root.span(), Span::new(0, u32::try_from(input.len()).unwrap()));
+        assert_eq!(self.text(), input);
+        for node in root.descendants() {
+            let span = node.span();
+            if node.kind().is_token() {
+                assert!(node.first_child().is_none(), "token {node:?} has children");
+                assert!(span.start < span.end, "token {node:?} is empty");
+                continue;
+            }
+            if node.first_child().is_none() {
+                assert_eq!(node, root, "node {node:?} has no tokens");
+                continue;
+            }
+            let mut end = span.start;
+            for child in node.children() {
+                assert_eq!(child.parent().as_ref(), Some(&node), "wrong parent of {child:?}");
+                assert_eq!(child.span().start, end, "gap before {child:?}");
+                end = child.span().end;
+            }
+            assert_eq!(end, span.end, "gap at end of {node:?}");
+        }
     }
 
     #[cfg(test)]
-    fn children(&self, node: NodeKey) -> impl Iterator<Item = NodeKey> {
-        let mut child = self.nodes[node].first;
+    pub(super) fn debug(&self, writer: &mut impl core::fmt::Write) -> core::fmt::Result {
+        let mut indent = 0;
+        for event in self.root().preorder() {
+            match event {
+                WalkEvent::Enter(node) => {
+                    write!(writer, "{: >indent$}{node:?}", "")?;
+                    if let GreenElement::Token(token) = node.green() {
+                        write!(writer, " {:?}", &*token.text)?;
+                    }
+                    writeln!(writer)?;
+                    indent += 2;
+                }
+                WalkEvent::Leave(_) => indent -= 2,
+            }
+        }
+        Ok(())
+    }
+}
+
+/// An immutable node in the green layer of a tree.
+#[derive(Debug, PartialEq, Eq)]
+pub(super) struct GreenNode {
+    /// The type of syntax that this node represents.
+    kind: Kind,
+    /// The length of the node's text, which is the sum of its children's.
+    length: u32,
+    /// The node's children, including tokens, in source order.
+    children: Box<[GreenElement]>,
+}
+
+impl GreenNode {
+    fn new(kind: Kind, children: Box<[GreenElement]>) -> Self {
+        let length = children.iter().map(GreenElement::length).sum();
+        Self { kind, length, children }
+    }
+}
+
+/// An immutable token in the green layer of a tree.
+#[derive(Debug, PartialEq, Eq)]
+pub(super) struct GreenToken {
+    /// The type of syntax that this token represents.
+    kind: Kind,
+    /// The token's text within the source.
+    text: Box<str>,
+}
+
+/// A node or token in the green layer of a tree.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(super) enum GreenElement {
+    Node(Arc<GreenNode>),
+    Token(Arc<GreenToken>),
+}
+
+impl GreenElement {
+    fn kind(&self) -> Kind {
+        match self {
+            Self::Node(node) => node.kind,
+            Self::Token(token) => token.kind,
+        }
+    }
+
+    fn length(&self) -> u32 {
+        match self {
+            Self::Node(node) => node.length,
+            Self::Token(token) => u32::try_from(token.text.len()).unwrap(),
+        }
+    }
+
+    fn children(&self) -> &[GreenElement] {
+        match self {
+            Self::Node(node) => &node.children,
+            Self::Token(_) => &[],
+        }
+    }
+
+    /// The address of the node or token, which identifies it once the tree
+    /// has been deduplicated.
+    fn address(&self) -> usize {
+        match self {
+            Self::Node(node) => Arc::as_ptr(node).addr(),
+            Self::Token(token) => Arc::as_ptr(token).addr(),
+        }
+    }
+}
+
+/// A node or token of a [`Tree`], which is a cursor to a node in the green
+/// layer of the tree, along with its parent and its offset in the source.
+///
+/// Two nodes are equal if they are at the same place in the same tree.
+#[derive(Clone)]
+pub(crate) struct Node(Rc<NodeData>);
+
+struct NodeData {
+    /// The node in the green layer.
+    green: GreenElement,
+    /// The parent of the node, or `None` for the root.
+    parent: Option<Node>,
+    /// The index of the node among the children of its parent.
+    index: usize,
+    /// The offset of the node within the source.
+    offset: u32,
+}
+
+impl Node {
+    /// The type of syntax that the node represents.
+    pub(crate) fn kind(&self) -> Kind {
+        self.0.green.kind()
+    }
+
+    /// The span of the node within the source.
+    pub(crate) fn span(&self) -> Span {
+        Span::new(self.0.offset, self.0.offset + self.0.green.length())
+    }
+
+    /// The node in the green layer.
+    pub(super) fn green(&self) -> &GreenElement {
+        &self.0.green
+    }
+
+    /// The text of the node within the source.
+    pub(crate) fn text(&self) -> String {
+        self.descendants()
+            .filter_map(|node| match node.green() {
+                GreenElement::Node(_) => None,
+                GreenElement::Token(token) => Some(token.text.clone()),
+            })
+            .collect()
+    }
+
+    /// The children of the node, including tokens, in source order.
+    pub(crate) fn children(&self) -> impl Iterator<Item = Node> + use<> {
+        let mut child = self.first_child();
         core::iter::from_fn(move || {
-            let mut res = child?;
-            child = self.nodes[res].next;
+            let res = child.take()?;
+            child = res.next_sibling();
             Some(res)
         })
     }
 
-    #[cfg(test)]
-    pub(super) fn debug(
-        &self,
-        writer: &mut impl core::fmt::Write,
-        input: &str,
-    ) -> core::fmt::Result {
-        let Some(root) = self.root else {
-            return Ok(());
-        };
-        self.debug_helper(writer, input, root, 0)
+    /// The child of the node at `index`, which is at `offset` in the source.
+    fn child(&self, index: usize, offset: u32) -> Option<Node> {
+        let green = self.0.green.children().get(index)?.clone();
+        Some(Node(Rc::new(NodeData { green, parent: Some(self.clone()), index, offset })))
     }
 
-    #[cfg(test)]
-    fn debug_helper(
-        &self,
-        writer: &mut impl core::fmt::Write,
-        input: &str,
-        node_key: NodeKey,
-        indent: usize,
-    ) -> core::fmt::Result {
-        let node = self.nodes[node_key];
-        write!(writer, "{: >indent$}{:?}@{}", "", node.kind, node.span)?;
-        if node.kind.is_token() {
-            write!(writer, " {:?}", &input[node.span])?;
+    /// The tree which this node is in, with the node replaced by
+    /// `replacement`. Everything else in the new tree is shared with the old
+    /// one.
+    pub(super) fn replace_with(&self, replacement: GreenElement) -> Tree {
+        let mut node = self.clone();
+        let mut green = replacement;
+        while let Some(parent) = node.parent() {
+            let mut children = parent.0.green.children().to_vec();
+            children[node.0.index] = green;
+            green = GreenElement::Node(Arc::new(GreenNode::new(parent.kind(), children.into())));
+            node = parent;
         }
-        writeln!(writer)?;
-        for child in self.children(node_key) {
-            self.debug_helper(writer, input, child, indent + 2)?;
+        match green {
+            GreenElement::Node(root) => Tree { root },
+            GreenElement::Token(_) => panic!("tried to replace the root node with a token"),
         }
-        Ok(())
     }
 }
 
-crate::structures::arena!(Node, pub(super) NodeArena, pub(super) NodeKey);
+impl PartialEq for Node {
+    fn eq(&self, other: &Self) -> bool {
+        Rc::ptr_eq(&self.0, &other.0)
+            || self.0.offset == other.0.offset
+                && self.0.green.address() == other.0.green.address()
+                && self.0.parent == other.0.parent
+    }
+}
 
-/// A node in a [`Tree`].
-#[derive(Clone, Copy, Debug, PartialEq, Eq)]
-pub(super) struct Node {
-    /// The type of syntax that this node represents.
-    kind: Kind,
-    /// The corresponding span within the original source.
-    span: Span,
-    /// The parent of the current node.
-    parent: Option<NodeKey>,
-    /// The previous sibling node.
-    previous: Option<NodeKey>,
-    /// The next sibling node.
-    next: Option<NodeKey>,
-    /// The first child node.
-    first: Option<NodeKey>,
-    /// The last child node.
-    last: Option<NodeKey>,
+impl Eq for Node {}
+
+impl core::fmt::Debug for Node {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "{:?}@{}", self.kind(), self.span())
+    }
 }
-const _: () = assert!(size_of::<Node>() == 32);
 
+/// Navigation between the nodes of a tree.
+#[cfg_attr(not(test), expect(dead_code))]
 impl Node {
-    /// The type of syntax that this node represents.
-    #[cfg(test)]
-    pub(super) fn kind(&self) -> Kind {
-        self.kind
+    pub(crate) fn parent(&self) -> Option<Node> {
+        self.0.parent.clone()
+    }
+
+    pub(crate) fn first_child(&self) -> Option<Node> {
+        self.child(0, self.0.offset)
+    }
+
+    pub(crate) fn last_child(&self) -> Option<Node> {
+        let last = self.0.green.children().last()?;
+        self.child(self.0.green.children().len() - 1, self.span().end - last.length())
+    }
+
+    pub(crate) fn next_sibling(&self) -> Option<Node> {
+        self.0.parent.as_ref()?.child(self.0.index + 1, self.span().end)
+    }
+
+    pub(crate) fn previous_sibling(&self) -> Option<Node> {
+        let parent = self.0.parent.as_ref()?;
+        let index = self.0.index.checked_sub(1)?;
+        parent.child(index, self.0.offset - parent.0.green.children()[index].length())
+    }
+
+    /// The node followed by its parent, its parent's parent, and so on up to
+    /// the root.
+    pub(crate) fn ancestors(&self) -> impl Iterator<Item = Node> + use<> {
+        core::iter::successors(Some(self.clone()), Node::parent)
+    }
+
+    /// The node followed by its siblings in `direction`.
+    pub(crate) fn siblings(&self, direction: Direction) -> impl Iterator<Item = Node> + use<> {
+        core::iter::successors(Some(self.clone()), move |node| match direction {
+            Direction::Next => node.next_sibling(),
+            Direction::Previous => node.previous_sibling(),
+        })
+    }
+
+    /// Walk the node and all of its descendants in source order, entering each
+    /// one before its children and leaving it after them.
+    pub(crate) fn preorder(&self) -> impl Iterator<Item = WalkEvent> + use<> {
+        let start = self.clone();
+        let mut next = Some(WalkEvent::Enter(self.clone()));
+        core::iter::from_fn(move || {
+            let event = next.take()?;
+            next = match &event {
+                WalkEvent::Enter(current) => Some(match current.first_child() {
+                    Some(child) => WalkEvent::Enter(child),
+                    None => WalkEvent::Leave(current.clone()),
+                }),
+                // the walk must not continue to the siblings of the start
+                WalkEvent::Leave(current) if *current == start => None,
+                WalkEvent::Leave(current) => match current.next_sibling() {
+                    Some(sibling) => Some(WalkEvent::Enter(sibling)),
+                    None => current.parent().map(WalkEvent::Leave),
+                },
+            };
+            Some(event)
+        })
+    }
+
+    /// The node and all of its descendants, in source order.
+    pub(crate) fn descendants(&self) -> impl Iterator<Item = Node> + use<> {
+        self.preorder().filter_map(|event| match event {
+            WalkEvent::Enter(node) => Some(node),
+            WalkEvent::Leave(_) => None,
+        })
+    }
+
+    /// The token or tokens within the node which touch `offset`. An offset
+    /// between two tokens touches both of them, so that, for example, a cursor
+    /// just after a name can still find the name.
+    pub(crate) fn token_at_offset(&self, offset: u32) -> TokenAtOffset {
+        let span = self.span();
+        if offset < span.start || offset > span.end {
+            return TokenAtOffset::None;
+        }
+        let left = self.descend_to_token(offset, Direction::Previous);
+        let right = self.descend_to_token(offset, Direction::Next);
+        match (left, right) {
+            (Some(left), Some(right)) if left == right => TokenAtOffset::Single(left),
+            (Some(left), Some(right)) => TokenAtOffset::Between(left, right),
+            _ => TokenAtOffset::None,
+        }
+    }
+
+    /// Descend to a token touching `offset`, going towards `bias` when two
+    /// children both touch it.
+    fn descend_to_token(&self, offset: u32, bias: Direction) -> Option<Node> {
+        let mut node = self.clone();
+        while !node.kind().is_token() {
+            // an empty node, such as the root of an empty file, has no token
+            // to descend to
+            let mut touching = node.children().filter(|child| {
+                let span = child.span();
+                span.start != span.end && span.start <= offset && offset <= span.end
+            });
+            node = match bias {
+                Direction::Previous => touching.next(),
+                Direction::Next => touching.last(),
+            }?;
+        }
+        Some(node)
+    }
+
+    /// The smallest node or token within the node which contains all of
+    /// `span`, or the node itself if the span is not within it.
+    pub(crate) fn covering_element(&self, span: Span) -> Node {
+        let mut node = self.clone();
+        while let Some(child) = node.children().find(|child| {
+            let child_span = child.span();
+            child_span.start != child_span.end
+                && child_span.start <= span.start
+                && span.end <= child_span.end
+        }) {
+            node = child;
+        }
+        node
+    }
+}
+
+/// The direction in which to iterate over siblings.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum Direction {
+    Next,
+    Previous,
+}
+
+/// An event in a walk over a tree, either reaching a node, or finishing with
+/// it and all of its descendants.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) enum WalkEvent {
+    Enter(Node),
+    Leave(Node),
+}
+
+/// The tokens which touch an offset in the source.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) enum TokenAtOffset {
+    /// The offset is outside the node, or the node has no tokens.
+    None,
+    /// The offset is within a single token.
+    Single(Node),
+    /// The offset is between two adjacent tokens.
+    Between(Node, Node),
+}
+
+impl TokenAtOffset {
+    /// The token which ends at the offset, if there are two.
+    pub(crate) fn left_biased(self) -> Option<Node> {
+        match self {
+            Self::None => None,
+            Self::Single(token) | Self::Between(token, _) => Some(token),
+        }
+    }
+
+    /// The token which starts at the offset, if there are two.
+    pub(crate) fn right_biased(self) -> Option<Node> {
+        match self {
+            Self::None => None,
+            Self::Single(token) | Self::Between(_, token) => Some(token),
+        }
     }
 }
 
 /// A builder for a [`Tree`], using a stack of currently-open nodes.
 #[derive(Debug)]
-pub(super) struct Builder {
-    /// The tree being built.
-    tree: Tree,
-    /// The parent of the current node.
-    parent: Option<NodeKey>,
-    /// The previous sibling of the current node.
-    previous: Option<NodeKey>,
+pub(super) struct Builder<'text> {
+    /// The text which the tree is built from.
+    text: &'text str,
+    /// The kind of each currently-open node, and the index of its first child
+    /// in `children`.
+    parents: Vec<(Kind, usize)>,
+    /// The children of the currently-open nodes, each node's after those of
+    /// its parent.
+    children: Vec<GreenElement>,
+    /// The root node, once it is closed.
+    root: Option<Arc<GreenNode>>,
     /// The current location.
     cursor: u32,
+    /// The nodes and tokens built so far, for deduplication.
+    cache: Cache<'text>,
 }
 
-impl Builder {
-    /// Construct a new builder with an empty tree.
-    pub(super) const fn new() -> Self {
-        Self { tree: Tree::new(), parent: None, previous: None, cursor: 0 }
+impl<'text> Builder<'text> {
+    /// Construct a new builder for a tree of `text`.
+    pub(super) fn new(text: &'text str) -> Self {
+        Self {
+            text,
+            parents: Vec::new(),
+            children: Vec::new(),
+            root: None,
+            cursor: 0,
+            cache: Cache::default(),
+        }
     }
 
     /// Finish building the tree.
     #[must_use]
     pub(super) fn build(self) -> Tree {
-        if self.tree.root.is_none() {
-            panic!("building tree with no root node")
-        } else if self.parent.is_some() {
-            panic!("building tree with unclosed nodes")
-        }
-        dbg!(&self.tree);
-        self.tree
-    }
-
-    /// Insert a node at the current location.
-    pub(super) fn insert(&mut self, kind: Kind, span: Span) -> NodeKey {
-        let node = Node {
-            kind,
-            span,
-            parent: self.parent,
-            previous: self.previous,
-            next: None,
-            first: None,
-            last: None,
-        };
-        let key = self.tree.nodes.alloc(node);
-
-        if let Some(parent_key) = self.parent {
-            let parent = &mut self.tree.nodes[parent_key];
-            if parent.first.is_none() {
-                parent.first = Some(key);
-            }
-            parent.last = Some(key);
-
-            parent.span.end = self.cursor;
-        } else if self.tree.root.is_none() {
-            self.tree.root = Some(key);
-        } else {
-            dbg!(&self);
-            panic!("building tree with multiple root nodes");
-        }
-
-        if let Some(previous_key) = self.previous {
-            self.tree.nodes[previous_key].next = self.tree.nodes[previous_key].next.or(Some(key));
-        }
-
-        key
+        assert!(self.parents.is_empty(), "building tree with unclosed nodes");
+        let Some(root) = self.root else { panic!("building tree with no root node") };
+        Tree { root }
     }
 
     /// Begin a new node at the current location.
     pub(super) fn open(&mut self, kind: Kind) {
-        let key = self.insert(kind, Span::new(self.cursor, self.cursor));
-        self.parent = Some(key);
+        assert!(
+            !self.parents.is_empty() || self.root.is_none(),
+            "building tree with multiple root nodes"
+        );
+        self.parents.push((kind, self.children.len()));
     }
 
     /// Close the current node.
     pub(super) fn close(&mut self) {
-        let current = self.tree.nodes[self.parent.expect("close called with no open nodes")];
-        self.previous = self.parent;
-        if let Some(parent_key) = current.parent {
-            let parent = &mut self.tree.nodes[parent_key];
-            parent.span = parent.span.join(current.span);
-            self.parent = Some(parent_key);
+        let (kind, first) = self.parents.pop().expect("close called with no open nodes");
+        let children = self.children.split_off(first);
+        let node = self.cache.node(kind, children);
+        if self.parents.is_empty() {
+            self.root = Some(node);
         } else {
-            self.parent = None;
+            self.children.push(GreenElement::Node(node));
         }
-        self.cursor = current.span.end;
     }
 
     /// Add a token to the current node.
     pub(super) fn token(&mut self, kind: Kind, length: u32) {
-        let Some(parent) = self.parent else {
-            panic!("adding token with no node");
-        };
+        assert!(!self.parents.is_empty(), "adding token with no node");
         let span = Span::new(self.cursor, self.cursor + length);
-        self.cursor += length;
-        self.tree.nodes[parent].span.end = span.end;
-        let key = self.insert(kind, span);
-        self.previous = Some(key);
+        self.cursor = span.end;
+        let token = self.cache.token(kind, &self.text[span]);
+        self.children.push(GreenElement::Token(token));
+    }
+}
+
+/// The nodes and tokens of a tree being built, so that syntax which appears
+/// more than once, such as every `Int`, is only stored once.
+#[derive(Debug, Default)]
+struct Cache<'text> {
+    tokens: HashMap<(Kind, &'text str), Arc<GreenToken>>,
+    /// Nodes by the addresses of their children, which identify the children
+    /// since they have already been deduplicated.
+    nodes: HashMap<(Kind, Vec<usize>), Arc<GreenNode>>,
+}
+
+impl<'text> Cache<'text> {
+    fn token(&mut self, kind: Kind, text: &'text str) -> Arc<GreenToken> {
+        let token = self
+            .tokens
+            .entry((kind, text))
+            .or_insert_with(|| Arc::new(GreenToken { kind, text: text.into() }));
+        Arc::clone(token)
+    }
+
+    fn node(&mut self, kind: Kind, children: Vec<GreenElement>) -> Arc<GreenNode> {
+        let addresses = children.iter().map(GreenElement::address).collect();
+        let node = self
+            .nodes
+            .entry((kind, addresses))
+            .or_insert_with(|| Arc::new(GreenNode::new(kind, children.into())));
+        Arc::clone(node)
     }
 }
 
@@ -194,7 +521,7 @@ impl Builder {
 mod tests {
     use self::Event::*;
     use super::Builder;
-    use crate::syntax::kind::Kind::*;
+    use crate::syntax::{kind::Kind::*, test_tree};
 
     #[derive(Debug, PartialEq, Eq)]
     enum Event {
@@ -204,34 +531,37 @@ mod tests {
     }
 
     fn walk_tree(tree: &super::Tree) -> impl Iterator<Item = (Event, crate::syntax::kind::Kind)> {
-        let mut current = tree.root;
+        let mut current = Some(tree.root());
         let mut event = Event::Next;
         core::iter::from_fn(move || {
-            let current_node = tree.nodes[current?];
+            let current_node = current.take()?;
             if event == Event::Up {
-                if let Some(next) = current_node.next {
-                    current = Some(next);
+                if let Some(next) = current_node.next_sibling() {
                     event = Event::Next;
-                    return Some((Event::Next, tree.nodes[next].kind));
+                    let kind = next.kind();
+                    current = Some(next);
+                    return Some((Event::Next, kind));
                 }
-            } else if let Some(first) = current_node.first {
-                current = Some(first);
+            } else if let Some(first) = current_node.first_child() {
                 event = Event::Down;
-                return Some((Event::Down, tree.nodes[first].kind));
-            } else if let Some(next) = current_node.next {
-                current = Some(next);
+                let kind = first.kind();
+                current = Some(first);
+                return Some((Event::Down, kind));
+            } else if let Some(next) = current_node.next_sibling() {
                 event = Event::Next;
-                return Some((Event::Next, tree.nodes[next].kind));
+                let kind = next.kind();
+                current = Some(next);
+                return Some((Event::Next, kind));
             }
-            current = current_node.parent;
+            current = current_node.parent();
             event = Event::Up;
-            Some((Event::Up, tree.nodes[current_node.parent?].kind))
+            Some((Event::Up, current.as_ref()?.kind()))
         })
     }
 
     #[test]
     fn empty_tree() {
-        let mut builder = Builder::new();
+        let mut builder = Builder::new("");
         builder.open(Root);
         builder.close();
         let tree = builder.build();
@@ -240,7 +570,7 @@ mod tests {
 
     #[test]
     fn only_tokens() {
-        let mut builder = Builder::new();
+        let mut builder = Builder::new("(hello)");
         builder.open(Root);
         builder.token(OpenParen, 1);
         builder.token(Ident, 5);
@@ -255,7 +585,7 @@ mod tests {
 
     #[test]
     fn only_nodes() {
-        let mut builder = Builder::new();
+        let mut builder = Builder::new("");
         builder.open(Root);
         builder.open(Fn);
         builder.open(ParamList);
@@ -275,7 +605,7 @@ mod tests {
 
     #[test]
     fn mix() {
-        let mut builder = Builder::new();
+        let mut builder = Builder::new("fnhello{123};");
         builder.open(Root);
         builder.open(Fn);
         builder.token(FnKw, 2);
@@ -307,17 +637,212 @@ mod tests {
         );
     }
 
+    /// The tree of `fn foo(a)`.
+    fn example() -> super::Tree {
+        let mut builder = Builder::new("fn foo(a)");
+        builder.open(Root);
+        builder.open(Fn);
+        builder.token(FnKw, 2);
+        builder.token(Whitespace, 1);
+        builder.token(Ident, 3);
+        builder.open(ParamList);
+        builder.token(OpenParen, 1);
+        builder.token(Ident, 1);
+        builder.token(CloseParen, 1);
+        builder.close();
+        builder.close();
+        builder.close();
+        builder.build()
+    }
+
+    fn find(tree: &super::Tree, kind: crate::syntax::kind::Kind, start: u32) -> super::Node {
+        tree.root()
+            .descendants()
+            .find(|node| node.kind() == kind && node.span().start == start)
+            .unwrap()
+    }
+
+    #[test]
+    fn preorder() {
+        use super::WalkEvent;
+
+        let tree = example();
+        let events = tree
+            .root()
+            .preorder()
+            .map(|event| match event {
+                WalkEvent::Enter(node) => (Down, node.kind()),
+                WalkEvent::Leave(node) => (Up, node.kind()),
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            events,
+            [
+                (Down, Root),
+                (Down, Fn),
+                (Down, FnKw),
+                (Up, FnKw),
+                (Down, Whitespace),
+                (Up, Whitespace),
+                (Down, Ident),
+                (Up, Ident),
+                (Down, ParamList),
+                (Down, OpenParen),
+                (Up, OpenParen),
+                (Down, Ident),
+                (Up, Ident),
+                (Down, CloseParen),
+                (Up, CloseParen),
+                (Up, ParamList),
+                (Up, Fn),
+                (Up, Root),
+            ]
+        );
+    }
+
+    #[test]
+    fn preorder_stays_within_node() {
+        let tree = example();
+        let name = find(&tree, Ident, 3);
+        assert_eq!(name.preorder().count(), 2);
+        let params = find(&tree, ParamList, 6);
+        assert_eq!(
+            params.descendants().map(|node| node.kind()).collect::<Vec<_>>(),
+            [ParamList, OpenParen, Ident, CloseParen]
+        );
+    }
+
+    #[test]
+    fn ancestors() {
+        let tree = example();
+        let param = find(&tree, Ident, 7);
+        assert_eq!(
+            param.ancestors().map(|node| node.kind()).collect::<Vec<_>>(),
+            [Ident, ParamList, Fn, Root]
+        );
+        let params = find(&tree, ParamList, 6);
+        assert_eq!(param.parent(), Some(params.clone()));
+        assert_eq!(params.first_child(), Some(find(&tree, OpenParen, 6)));
+        assert_eq!(params.last_child(), Some(find(&tree, CloseParen, 8)));
+    }
+
+    #[test]
+    fn siblings() {
+        use super::Direction;
+
+        let tree = example();
+        let name = find(&tree, Ident, 3);
+        assert_eq!(
+            name.siblings(Direction::Next).map(|node| node.kind()).collect::<Vec<_>>(),
+            [Ident, ParamList]
+        );
+        assert_eq!(
+            name.siblings(Direction::Previous).map(|node| node.kind()).collect::<Vec<_>>(),
+            [Ident, Whitespace, FnKw]
+        );
+    }
+
+    #[test]
+    fn token_at_offset() {
+        use super::TokenAtOffset;
+
+        let tree = example();
+        let root = tree.root();
+        let name = find(&tree, Ident, 3);
+        assert_eq!(root.token_at_offset(0), TokenAtOffset::Single(find(&tree, FnKw, 0)));
+        assert_eq!(root.token_at_offset(4), TokenAtOffset::Single(name.clone()));
+        assert_eq!(
+            root.token_at_offset(6),
+            TokenAtOffset::Between(name.clone(), find(&tree, OpenParen, 6))
+        );
+        assert_eq!(root.token_at_offset(6).left_biased(), Some(name));
+        assert_eq!(root.token_at_offset(6).right_biased(), Some(find(&tree, OpenParen, 6)));
+        assert_eq!(root.token_at_offset(9), TokenAtOffset::Single(find(&tree, CloseParen, 8)));
+        assert_eq!(root.token_at_offset(10), TokenAtOffset::None);
+    }
+
+    #[test]
+    fn token_at_offset_skips_empty_nodes() {
+        use super::TokenAtOffset;
+
+        let mut builder = Builder::new("a;");
+        builder.open(Root);
+        builder.token(Ident, 1);
+        builder.open(Error);
+        builder.close();
+        builder.token(Semi, 1);
+        builder.close();
+        let tree = builder.build();
+        assert_eq!(
+            tree.root().token_at_offset(1),
+            TokenAtOffset::Between(find(&tree, Ident, 0), find(&tree, Semi, 1))
+        );
+    }
+
+    #[test]
+    fn covering_element() {
+        use crate::source::Span;
+
+        let tree = example();
+        let root = tree.root();
+        assert_eq!(root.covering_element(Span::new(3, 5)), find(&tree, Ident, 3));
+        assert_eq!(root.covering_element(Span::new(4, 4)), find(&tree, Ident, 3));
+        assert_eq!(root.covering_element(Span::new(7, 9)), find(&tree, ParamList, 6));
+        assert_eq!(root.covering_element(Span::new(0, 9)), find(&tree, Fn, 0));
+        assert_eq!(root.covering_element(Span::new(0, 20)), root);
+    }
+
+    #[test]
+    fn repeated_syntax_is_shared() {
+        let tree = test_tree("fn foo(a: Int, b: Int) = a;");
+        let types = tree.root().descendants().filter(|node| node.kind() == TypePath);
+        let addresses = types.map(|node| node.green().address()).collect::<Vec<_>>();
+        assert_eq!(addresses.len(), 2);
+        assert_eq!(addresses[0], addresses[1]);
+    }
+
+    #[test]
+    fn unchanged_nodes_are_equal() {
+        let before = test_tree("fn foo() = 1;\nfn bar() = 2;");
+        let after = test_tree("fn foo() = 10;\nfn bar() = 2;");
+        assert_ne!(before, after);
+        let bar =
+            |tree: &super::Tree| tree.root().children().filter(|node| node.kind() == Fn).nth(1);
+        let (before_bar, after_bar) = (bar(&before).unwrap(), bar(&after).unwrap());
+        assert_ne!(before_bar.span(), after_bar.span());
+        assert_eq!(before_bar.green(), after_bar.green());
+        assert_eq!(test_tree("fn foo() = 1;"), test_tree("fn foo() = 1;"));
+    }
+
+    #[test]
+    fn replace_with_shares_the_rest() {
+        let tree = example();
+        let name = find(&tree, Ident, 3);
+        let replacement = {
+            let mut builder = Builder::new("bar");
+            builder.open(Root);
+            builder.token(Ident, 3);
+            builder.close();
+            builder.build()
+        };
+        let token = replacement.root().first_child().unwrap();
+        let replaced = name.replace_with(token.green().clone());
+        assert_eq!(replaced.text(), "fn bar(a)");
+        let params = find(&replaced, ParamList, 6);
+        assert_eq!(params.green().address(), find(&tree, ParamList, 6).green().address());
+    }
+
     #[test]
     #[should_panic = "building tree with no root node"]
     fn no_root_node() {
-        let builder = Builder::new();
+        let builder = Builder::new("");
         let _ = builder.build();
     }
 
     #[test]
     #[should_panic = "building tree with multiple root nodes"]
     fn multiple_root_nodes() {
-        let mut builder = Builder::new();
+        let mut builder = Builder::new("");
         builder.open(Root);
         builder.close();
         builder.open(Root);
@@ -328,7 +853,7 @@ mod tests {
     #[test]
     #[should_panic = "building tree with unclosed nodes"]
     fn single_unclosed_node() {
-        let mut builder = Builder::new();
+        let mut builder = Builder::new("");
         builder.open(Root);
         let _ = builder.build();
     }
@@ -336,7 +861,7 @@ mod tests {
     #[test]
     #[should_panic = "building tree with unclosed nodes"]
     fn multiple_unclosed_nodes() {
-        let mut builder = Builder::new();
+        let mut builder = Builder::new("");
         builder.open(Root);
         builder.open(Fn);
         builder.close();
@@ -42,7 +42,8 @@ fn build_inner(
             file.display()
         ))]);
     };
-    let source = crate::source::Source::new(db, vec![crate::source::File::new(db, file, text)]);
+    let source =
+        crate::source::Source::new(db, vec![crate::source::File::new(db, file, text, None)]);
     let diagnostics = crate::compile(db, source);
     match diagnostics.is_empty() {
         true => Ok(()),